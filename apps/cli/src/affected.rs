@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Command;
+
+use crate::graph::{ProjectGraph, ProjectNode};
+use crate::project::Project;
+
+/// Project-level config files: changing them may affect every app and package
+const PROJECT_CONFIG_FILES: [&str; 2] = ["knot.yml", "knot.yaml"];
+
+/// Files changed since the merge base of `since` and HEAD, including uncommitted and
+/// untracked files, relative to the project root
+pub fn changed_files(project_root: &Path, since: &str) -> Result<Vec<String>> {
+    let merge_base = git(project_root, &["merge-base", since, "HEAD"])
+        .with_context(|| format!("Cannot compare with '{}'\n💡 Check that the ref exists, e.g. run 'git fetch' for remote branches", since))?;
+    let merge_base = merge_base.trim();
+
+    let mut files: BTreeSet<String> = BTreeSet::new();
+    files.extend(lines(&git(project_root, &["diff", "--name-only", "--relative", merge_base])?));
+    files.extend(lines(&git(project_root, &["ls-files", "--others", "--exclude-standard"])?));
+    Ok(files.into_iter().collect())
+}
+
+/// Apps and packages containing a changed file, plus everything depending on them.
+/// A change to knot.yml affects the whole project.
+pub fn affected_nodes(project: &Project, graph: &ProjectGraph, changed_files: &[String]) -> BTreeSet<ProjectNode> {
+    let changed: Vec<ProjectNode> = changed_nodes(project, graph, changed_files).into_iter().collect();
+    graph.with_dependents(&changed)
+}
+
+/// Apps and packages containing a changed file, or all of them when knot.yml changed
+pub fn changed_nodes(project: &Project, graph: &ProjectGraph, changed_files: &[String]) -> BTreeSet<ProjectNode> {
+    if changed_files.iter().any(|file| PROJECT_CONFIG_FILES.contains(&file.as_str())) {
+        return graph.all_nodes();
+    }
+
+    changed_files
+        .iter()
+        .filter_map(|file| owning_node(project, file))
+        .collect()
+}
+
+/// The app or package whose directory contains `file` (relative to the project root)
+fn owning_node(project: &Project, file: &str) -> Option<ProjectNode> {
+    let mut components = file.split('/');
+    let (kind, name) = (components.next()?, components.next()?);
+    components.next()?;
+
+    match kind {
+        "apps" if project.apps.contains_key(name) => Some(ProjectNode::App(name.to_string())),
+        "packages" if project.packages.contains_key(name) => Some(ProjectNode::Package(name.to_string())),
+        _ => None,
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git\n💡 --affected needs git installed and the project inside a git repository")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn lines(output: &str) -> impl Iterator<Item = String> + '_ {
+    output.lines().filter(|line| !line.is_empty()).map(|line| line.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_changes_map_to_packages_and_their_dependents() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "knot.yml", "name: demo\n");
+        write(root, "packages/types/package.yml", "name: types\nversion: 1.0.0\n");
+        write(root, "packages/utils/package.yml", "name: utils\nversion: 1.0.0\ndependencies:\n  - types\n");
+        write(root, "apps/web/app.yml", "name: web\npackages:\n  - utils\n");
+        write(root, "apps/docs/app.yml", "name: docs\n");
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &["add", "."],
+            &["-c", "user.name=knot", "-c", "user.email=knot@example.com", "commit", "-q", "-m", "init"],
+        ] {
+            git(root, args).unwrap();
+        }
+
+        let project = Project::find_and_load(root).unwrap();
+        let graph = ProjectGraph::from_project(&project);
+        assert!(changed_files(root, "main").unwrap().is_empty());
+
+        write(root, "packages/types/index.ts", "export type Id = string;\n");
+        let changed = changed_files(root, "main").unwrap();
+        assert_eq!(changed, vec!["packages/types/index.ts".to_string()]);
+
+        let affected = affected_nodes(&project, &graph, &changed);
+        let names: Vec<&str> = affected.iter().map(|node| node.name()).collect();
+        assert_eq!(names, vec!["types", "utils", "web"]);
+
+        let everything = affected_nodes(&project, &graph, &["knot.yml".to_string()]);
+        assert_eq!(everything.len(), 4);
+    }
+}
@@ -3,6 +3,8 @@
 use anyhow::Result;
 use std::path::Path;
 
+// Shared with the knot binary, which uses the parts this program does not
+#[allow(dead_code)]
 #[path = "../config.rs"]
 mod config;
 
@@ -4,6 +4,8 @@
 use anyhow::Result;
 
 /// Re-export the config types and error handling from the main module
+// Shared with the knot binary, which uses the parts this program does not
+#[allow(dead_code)]
 #[path = "../config.rs"]
 mod config;
 
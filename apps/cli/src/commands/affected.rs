@@ -0,0 +1,55 @@
+use anyhow::Result;
+use console::style;
+use serde_json::json;
+
+use crate::affected;
+use crate::commands::common::display_info;
+use crate::graph::ProjectGraph;
+use crate::project::Project;
+
+/// List the apps and packages affected by changes since `since`, as text or JSON for CI
+pub fn show_affected(since: &str, as_json: bool) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let graph = ProjectGraph::from_project(&project);
+
+    let changed_files = affected::changed_files(&project.root, since)?;
+    let nodes = graph.topological_order(&affected::affected_nodes(&project, &graph, &changed_files))?;
+
+    if as_json {
+        let affected: Vec<_> = nodes
+            .iter()
+            .map(|node| {
+                json!({
+                    "name": node.name(),
+                    "kind": node.kind(),
+                    "path": node.dir(std::path::Path::new("")).to_string_lossy(),
+                })
+            })
+            .collect();
+        let output = json!({
+            "since": since,
+            "changed_files": changed_files,
+            "affected": affected,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!(
+        "🔍 {} changed file(s) since '{}'",
+        changed_files.len(),
+        style(since).cyan()
+    );
+    if nodes.is_empty() {
+        display_info("No apps or packages are affected");
+        return Ok(());
+    }
+
+    println!("📋 Affected ({}):", nodes.len());
+    for node in &nodes {
+        let icon = if node.kind() == "app" { "📱" } else { "📦" };
+        println!("  {} {} {}", icon, node.name(), style(format!("({})", node.kind())).dim());
+    }
+    Ok(())
+}
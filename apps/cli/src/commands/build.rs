@@ -0,0 +1,132 @@
+use anyhow::Result;
+use console::style;
+use std::path::Path;
+
+use crate::commands::common::display_info;
+use crate::config::Script;
+use crate::filter::Filter;
+use crate::graph::{ProjectGraph, ProjectNode};
+use crate::project::Project;
+use crate::tasks::scheduler::{self, SchedulerOptions};
+use crate::tasks::{self, summary};
+
+/// Build apps after the local packages they depend on. Without app names, builds the
+/// current app when run inside one, otherwise every app with a build command. A non-empty
+/// `filter` instead selects the apps and packages to build, skipping those without one.
+pub async fn build_apps(app_names: &[String], filter: &Filter, options: &SchedulerOptions) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let graph = ProjectGraph::from_project(&project);
+
+    let roots: Vec<ProjectNode> = if !filter.is_empty() {
+        let selected: Vec<ProjectNode> = filter
+            .select(&project, &graph)?
+            .into_iter()
+            .filter(|node| build_script(&project, node).is_some())
+            .collect();
+        println!("🔨 Building {} selected app(s) and package(s)...", selected.len());
+        selected
+    } else if !app_names.is_empty() {
+        for app_name in app_names {
+            if !project.apps.contains_key(app_name) {
+                anyhow::bail!(
+                    "App '{}' not found\n💡 Available apps: {}\n💡 Create it with: knot init:app {}",
+                    app_name,
+                    project.get_app_names().join(", "),
+                    app_name
+                );
+            }
+        }
+        app_names.iter().cloned().map(ProjectNode::App).collect()
+    } else if let Some(app_name) = current_app(&current_dir, &project) {
+        vec![ProjectNode::App(app_name)]
+    } else {
+        let mut apps: Vec<String> = project
+            .apps
+            .iter()
+            .filter(|(_, config)| config.build_command().is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        apps.sort();
+
+        println!("🔨 Building all apps for project '{}'...", style(&project.config.name).cyan());
+        println!("📋 Found {} app(s) with build commands", apps.len());
+        apps.into_iter().map(ProjectNode::App).collect()
+    };
+
+    if roots.is_empty() {
+        display_info("No apps with build commands found");
+        display_info("Add a build command to an app.yml, e.g. build: \"npm run build\"");
+        return Ok(());
+    }
+
+    let order = graph.topological_order(&graph.with_dependencies(&roots))?;
+
+    let mut roots = Vec::new();
+    for node in order {
+        if build_script(&project, &node).is_none() {
+            if let ProjectNode::App(app_name) = &node {
+                anyhow::bail!(
+                    "App '{}' has no build command\n💡 Add one to apps/{}/app.yml\n💡 Example: build: \"npm run build\"",
+                    app_name, app_name
+                );
+            }
+            continue;
+        }
+        roots.push((node, "build".to_string()));
+    }
+    let tasks = tasks::expand_tasks(&project, &graph, roots, "build", &[], |node, name| match name {
+        "build" => build_script(&project, node),
+        _ => tasks::node_script(&project, node, name).cloned(),
+    })?;
+
+    let cache = options.use_cache.then(|| project.task_cache()).transpose()?;
+    let results = scheduler::run_tasks(&tasks, &graph, cache.as_ref(), options).await?;
+    summary::print_summary(&results);
+
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|result| !result.is_success())
+        .map(|result| result.task.label())
+        .collect();
+    if !failed.is_empty() {
+        return Err(tasks::ExitError {
+            code: tasks::worst_exit_code(&results),
+            message: format!(
+                "Build did not succeed for: {}\n💡 Check the prefixed output above for details",
+                failed.join(", ")
+            ),
+        }
+        .into());
+    }
+
+    println!("🎉 All apps built successfully!");
+    Ok(())
+}
+
+/// App whose directory contains `current_dir`, if any
+pub fn current_app(current_dir: &Path, project: &Project) -> Option<String> {
+    let relative = current_dir.strip_prefix(&project.root).ok()?;
+    let mut components = relative.components();
+    if components.next()?.as_os_str() != "apps" {
+        return None;
+    }
+    let app_name = components.next()?.as_os_str().to_string_lossy().into_owned();
+    project.apps.contains_key(&app_name).then_some(app_name)
+}
+
+/// Apps use their `build` field, falling back to their `build` script; packages use their `build` script
+fn build_script(project: &Project, node: &ProjectNode) -> Option<Script> {
+    let (build, scripts) = match node {
+        ProjectNode::App(name) => {
+            let app = project.apps.get(name)?;
+            (app.build.as_ref(), app.scripts.as_ref())
+        }
+        ProjectNode::Package(name) => (None, project.packages.get(name)?.scripts.as_ref()),
+    };
+
+    match build {
+        Some(command) => Some(Script::Command(command.clone())),
+        None => scripts?.get("build").cloned(),
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use console::style;
+
+use crate::commands::common::{display_info, display_success};
+use crate::project::Project;
+use crate::validation::sanitize_input;
+
+pub async fn cache_stats() -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let cache = project.resolution_cache()?;
+
+    cache.cache_stats().await.print_detailed_report();
+    Ok(())
+}
+
+pub async fn cache_clear() -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let cache = project.resolution_cache()?;
+
+    let before = cache.cache_stats().await;
+    cache.clear_cache().await?;
+
+    display_success(&format!(
+        "Cleared {} cached resolution(s) from {}",
+        before.disk_entries,
+        before.disk_path.display()
+    ));
+    Ok(())
+}
+
+/// Remove cached task outputs and, unless `tasks_only`, cached resolutions
+pub async fn cache_clean(tasks_only: bool) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+
+    if !tasks_only {
+        cache_clear().await?;
+    }
+
+    let task_cache = project.task_cache()?;
+    let removed = task_cache.clean()?;
+    if removed == 0 {
+        display_info("No cached task outputs found");
+    } else {
+        display_success(&format!(
+            "Removed {} cached task run(s) from {}",
+            removed,
+            task_cache.dir().display()
+        ));
+    }
+    Ok(())
+}
+
+pub async fn cache_gc() -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let cache = project.resolution_cache()?;
+
+    let before = cache.cache_stats().await.disk_entries;
+    cache.cleanup_expired().await?;
+    let after = cache.cache_stats().await.disk_entries;
+
+    if before == after {
+        display_info("No expired cache entries found");
+    } else {
+        display_success(&format!(
+            "Removed {} expired cache entr{} ({} remaining)",
+            before - after,
+            if before - after == 1 { "y" } else { "ies" },
+            after
+        ));
+    }
+    Ok(())
+}
+
+pub async fn cache_invalidate(package_name: &str) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let cache = project.resolution_cache()?;
+
+    let package_name = sanitize_input(package_name);
+    if package_name.is_empty() {
+        anyhow::bail!("Package name cannot be empty\n💡 Usage: knot cache invalidate <package>");
+    }
+
+    let before = cache.cache_stats().await.disk_entries;
+    cache.invalidate_package_cache(&package_name).await?;
+    let removed = before - cache.cache_stats().await.disk_entries;
+
+    if removed == 0 {
+        display_info(&format!(
+            "No cached resolutions reference '{}'",
+            style(&package_name).cyan()
+        ));
+    } else {
+        display_success(&format!(
+            "Invalidated {} cached resolution(s) referencing '{}'",
+            removed,
+            style(&package_name).cyan()
+        ));
+    }
+    Ok(())
+}
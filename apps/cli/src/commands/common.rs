@@ -7,6 +7,8 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use crate::project::Project;
+use crate::staging;
+use crate::tasks::process::{self, Signal};
 
 // Check if running in interactive environment
 pub fn is_interactive() -> bool {
@@ -306,15 +308,56 @@ pub fn format_api_error(status: reqwest::StatusCode, response_text: &str) -> Str
     }
 }
 
-// Handle Ctrl+C gracefully
+// Handle Ctrl+C (and SIGTERM) gracefully: running scripts get the signal and a grace period
+// to exit before they are killed, and packages being extracted are rolled back. A second
+// signal kills everything at once.
 pub fn setup_ctrl_c_handler() -> Result<()> {
+    let (sender, signals) = std::sync::mpsc::channel();
+
+    let interrupt = sender.clone();
     ctrlc::set_handler(move || {
-        println!("\n{}", style("❌ Operation cancelled by user").red());
-        std::process::exit(130); // Standard exit code for Ctrl+C
+        let _ = interrupt.send(Signal::Interrupt);
     })?;
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::spawn(async move {
+            while terminate.recv().await.is_some() {
+                let _ = sender.send(Signal::Terminate);
+            }
+        });
+    }
+
+    std::thread::spawn(move || {
+        let Ok(signal) = signals.recv() else {
+            return;
+        };
+        std::thread::spawn(move || {
+            if signals.recv().is_ok() {
+                process::kill_all();
+                cancel(signal);
+            }
+        });
+
+        crate::tasks::dashboard::restore_terminal();
+        if process::is_running() {
+            println!(
+                "\n{}",
+                style("⏹️  Stopping running scripts (press Ctrl+C again to kill them)...").yellow()
+            );
+        }
+        process::stop_all(signal);
+        cancel(signal);
+    });
     Ok(())
 }
 
+fn cancel(signal: Signal) -> ! {
+    staging::roll_back();
+    println!("\n{}", style("❌ Operation cancelled by user").red());
+    std::process::exit(signal.exit_code());
+}
+
 // Clear the current line and move cursor to beginning
 #[allow(dead_code)]
 pub fn clear_line() {
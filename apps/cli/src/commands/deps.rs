@@ -6,7 +6,7 @@ use crate::dependency::{
     DependencyResolver,
     PackageId, PackageVersion, DependencySpec, ResolutionContext, ResolutionStrategy,
 };
-use crate::dependency::registry::{LocalPackageRegistry, RemotePackageRegistry};
+use crate::dependency::registry::LocalPackageRegistry;
 use crate::project::Project;
 use crate::validation::{validate_package_spec, validate_app_name, sanitize_input};
 
@@ -255,10 +255,10 @@ async fn create_resolver(project: &Project) -> Result<DependencyResolver> {
     };
     
     let local_registry = LocalPackageRegistry::new(project.root.join("packages"));
-    let remote_registry = RemotePackageRegistry::new("https://knot.space".to_string());
-    let cache_dir = project.root.join(".knot").join("cache");
+    let remote_registry = Project::remote_registry();
+    let cache = project.resolution_cache()?;
     
-    Ok(DependencyResolver::new(context, local_registry, remote_registry, cache_dir))
+    Ok(DependencyResolver::new(context, local_registry, remote_registry, cache))
 }
 
 fn detect_current_app(current_dir: &std::path::Path, project: &Project) -> Result<String> {
@@ -466,8 +466,8 @@ async fn resolve_app_dependencies(
     let mut resolver = DependencyResolver::new(
         context,
         LocalPackageRegistry::new(project.root.join("packages")),
-        RemotePackageRegistry::new("https://knot.space".to_string()),
-        project.root.join(".knot").join("cache"),
+        Project::remote_registry(),
+        project.resolution_cache()?,
     );
     
     match resolver.resolve_dependencies(deps).await {
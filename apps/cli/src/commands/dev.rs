@@ -0,0 +1,403 @@
+use anyhow::{Context, Result};
+use console::style;
+use std::collections::HashMap;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use crate::commands::build::current_app;
+use crate::commands::common::display_info;
+use crate::config::{DevConfig, ReadyCheck};
+use crate::graph::ProjectNode;
+use crate::project::Project;
+use crate::tasks::output::{OutputMode, TaskOutput};
+use crate::tasks::{self, process, Task};
+
+/// Script started by `knot dev` in every app
+const DEV_SCRIPT: &str = "dev";
+
+/// Pause before restarting a dev script that crashed, doubled after every further crash
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Crashes in a row after which a dev script is no longer restarted
+const MAX_RESTARTS: u32 = 5;
+
+/// How long a dev script must run before its earlier crashes are forgotten
+const STABLE_RUN: Duration = Duration::from_secs(30);
+
+/// Pause between attempts to connect to a readiness port
+const PORT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Readiness {
+    Starting,
+    Ready,
+    /// Exited or timed out before becoming ready; apps depending on it are not started
+    Failed,
+}
+
+/// The dev script of one app
+struct Service {
+    app: String,
+    task: Task,
+    config: DevConfig,
+    output: TaskOutput,
+}
+
+/// Start the `dev` script of apps concurrently with prefixed output. Without app names, starts
+/// the current app when run inside one, otherwise every app with a `dev` script. Apps listed in
+/// `dev.depends_on` are started first and must pass their readiness check. Ctrl-C stops every
+/// script together.
+pub async fn dev_apps(app_names: &[String]) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+
+    let targets = if !app_names.is_empty() {
+        app_names.to_vec()
+    } else if let Some(app_name) = current_app(&current_dir, &project) {
+        vec![app_name]
+    } else {
+        let mut apps: Vec<String> = project
+            .apps
+            .keys()
+            .filter(|app| dev_script(&project, app).is_some())
+            .cloned()
+            .collect();
+        apps.sort();
+        apps
+    };
+
+    if targets.is_empty() {
+        display_info("No app defines a 'dev' script");
+        display_info("Add one to an app.yml, e.g. scripts: { dev: \"npm run dev\" }");
+        return Ok(());
+    }
+
+    let order = start_order(&project, &targets)?;
+    let width = order.iter().map(|app| app.len()).max().unwrap_or(0);
+    println!("🚀 Starting {} app(s): {}", order.len(), style(order.join(" → ")).cyan());
+
+    let mut states: HashMap<String, watch::Receiver<Readiness>> = HashMap::new();
+    let mut running = JoinSet::new();
+    for (index, app) in order.iter().enumerate() {
+        let Some(script) = dev_script(&project, app) else {
+            continue;
+        };
+        let config = project.apps[app].dev.clone().unwrap_or_default();
+        let dependencies = config
+            .depends_on
+            .iter()
+            .filter_map(|dependency| Some((dependency.clone(), states.get(dependency)?.clone())))
+            .collect();
+        let (ready, state) = watch::channel(Readiness::Starting);
+        states.insert(app.clone(), state);
+
+        let service = Service {
+            app: app.clone(),
+            task: Task::from_script(ProjectNode::App(app.clone()), DEV_SCRIPT, script, &[], &project)?,
+            config,
+            output: TaskOutput::new(app, index, width, OutputMode::Interleaved),
+        };
+        running.spawn(run_service(service, dependencies, ready));
+    }
+
+    let mut failed = Vec::new();
+    while let Some(joined) = running.join_next().await {
+        let (app, success) = joined?;
+        if !success {
+            failed.push(app);
+        }
+    }
+
+    if !failed.is_empty() {
+        failed.sort();
+        anyhow::bail!(
+            "Dev script did not run successfully for: {}\n💡 Check the prefixed output above for details",
+            failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn dev_script<'a>(project: &'a Project, app: &str) -> Option<&'a crate::config::Script> {
+    tasks::node_script(project, &ProjectNode::App(app.to_string()), DEV_SCRIPT)
+}
+
+/// `targets` plus the apps they (indirectly) list in `dev.depends_on`, dependencies first
+fn start_order(project: &Project, targets: &[String]) -> Result<Vec<String>> {
+    fn visit(project: &Project, app: &str, visiting: &mut Vec<String>, order: &mut Vec<String>) -> Result<()> {
+        if order.iter().any(|started| started == app) {
+            return Ok(());
+        }
+        if visiting.iter().any(|visited| visited == app) {
+            visiting.push(app.to_string());
+            anyhow::bail!(
+                "Circular dev.depends_on between apps: {}\n💡 Remove one of the depends_on entries",
+                visiting.join(" → ")
+            );
+        }
+
+        let config = project.apps.get(app).ok_or_else(|| {
+            anyhow::anyhow!(
+                "App '{}' not found\n💡 Available apps: {}",
+                app,
+                project.get_app_names().join(", ")
+            )
+        })?;
+        if dev_script(project, app).is_none() {
+            anyhow::bail!(
+                "App '{}' has no 'dev' script\n💡 Add one to apps/{}/app.yml\n💡 Example: scripts:\n    dev: \"npm run dev\"",
+                app, app
+            );
+        }
+
+        visiting.push(app.to_string());
+        for dependency in config.dev.iter().flat_map(|dev| &dev.depends_on) {
+            visit(project, dependency, visiting, order)?;
+        }
+        visiting.pop();
+        order.push(app.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    for target in targets {
+        visit(project, target, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+/// Start the service once its dependencies are ready and keep it running, restarting it after
+/// it crashes when configured to, with growing delays until [`MAX_RESTARTS`] crashes in a row.
+/// Returns the app and whether its script succeeded.
+async fn run_service(
+    service: Service,
+    dependencies: Vec<(String, watch::Receiver<Readiness>)>,
+    ready: watch::Sender<Readiness>,
+) -> (String, bool) {
+    let ready = Arc::new(ready);
+    for (dependency, mut state) in dependencies {
+        let state = state.wait_for(|state| *state != Readiness::Starting).await.map(|state| *state);
+        if !matches!(state, Ok(Readiness::Ready)) {
+            service
+                .output
+                .line(&style(format!("Not started: '{}' did not become ready", dependency)).red().to_string());
+            ready.send_replace(Readiness::Failed);
+            return (service.app, false);
+        }
+    }
+
+    let mut crashes = 0;
+    loop {
+        let started = Instant::now();
+        let status = match run_once(&service, &ready).await {
+            Ok(status) => status,
+            Err(e) => {
+                service.output.line(&style(format!("{:#}", e)).red().to_string());
+                ready.send_replace(Readiness::Failed);
+                return (service.app, false);
+            }
+        };
+        let code = status.code().unwrap_or(-1);
+
+        if service.config.restart && !status.success() {
+            if started.elapsed() >= STABLE_RUN {
+                crashes = 0;
+            }
+            if crashes < MAX_RESTARTS {
+                let delay = restart_delay(crashes);
+                crashes += 1;
+                service.output.line(
+                    &style(format!(
+                        "Exited with code {}, restarting in {}s ({}/{})...",
+                        code,
+                        delay.as_secs(),
+                        crashes,
+                        MAX_RESTARTS
+                    ))
+                    .yellow()
+                    .to_string(),
+                );
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            service.output.line(
+                &style(format!("Exited with code {} after {} restarts, giving up", code, MAX_RESTARTS))
+                    .red()
+                    .to_string(),
+            );
+            ready.send_replace(Readiness::Failed);
+            return (service.app, false);
+        }
+
+        if *ready.borrow() == Readiness::Starting {
+            ready.send_replace(Readiness::Failed);
+        }
+        let message = format!("Exited with code {}", code);
+        if status.success() {
+            service.output.line(&style(message).dim().to_string());
+        } else {
+            service.output.line(&style(message).red().to_string());
+        }
+        return (service.app, status.success());
+    }
+}
+
+/// Pause before the restart following `crashes` earlier crashes in a row
+fn restart_delay(crashes: u32) -> Duration {
+    RESTART_DELAY * 2u32.pow(crashes)
+}
+
+/// Run the dev script until it exits, reporting readiness along the way
+async fn run_once(service: &Service, ready: &Arc<watch::Sender<Readiness>>) -> Result<ExitStatus> {
+    let task = &service.task;
+    let output = &service.output;
+    let check = service.config.ready.clone();
+    let pattern = check
+        .as_ref()
+        .and_then(|check| check.log.as_deref())
+        .map(regex::Regex::new)
+        .transpose()?;
+
+    let mut command = tasks::shell_command(task.shell.as_deref(), &task.command);
+    if task.hermetic {
+        command.env_clear();
+    }
+    command
+        .envs(&task.env)
+        .current_dir(&task.working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    process::isolate(&mut command);
+
+    output.line(&style(format!("$ {}", task.command)).dim().to_string());
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start '{}' in app '{}'", task.command, service.app))?;
+    let pid = child.id();
+    if let Some(pid) = pid {
+        process::register(pid);
+    }
+
+    let on_line = {
+        let (ready, output) = (ready.clone(), output.clone());
+        move |line: &str| {
+            if pattern.as_ref().is_some_and(|pattern| pattern.is_match(line)) {
+                mark_ready(&ready, &output);
+            }
+        }
+    };
+    let stdout = output.forward_with(child.stdout.take(), on_line.clone());
+    let stderr = output.forward_with(child.stderr.take(), on_line);
+
+    let readiness = match check {
+        Some(check) => Some(tokio::spawn(wait_until_ready(check, ready.clone(), output.clone()))),
+        None => {
+            mark_ready(ready, output);
+            None
+        }
+    };
+
+    let status = child.wait().await;
+    if let Some(pid) = pid {
+        process::unregister(pid);
+    }
+    process::halt_if_stopping().await;
+    let _ = tokio::join!(stdout, stderr);
+    if let Some(readiness) = readiness {
+        readiness.abort();
+    }
+    Ok(status?)
+}
+
+/// Mark the service ready once its port accepts connections, or fail it when neither the port
+/// nor the log pattern signal readiness in time
+async fn wait_until_ready(check: ReadyCheck, ready: Arc<watch::Sender<Readiness>>, output: TaskOutput) {
+    let port_open = async {
+        let Some(port) = check.port else {
+            return std::future::pending().await;
+        };
+        while tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err() {
+            tokio::time::sleep(PORT_POLL_INTERVAL).await;
+        }
+    };
+    let mut state = ready.subscribe();
+
+    tokio::select! {
+        _ = port_open => mark_ready(&ready, &output),
+        _ = state.wait_for(|state| *state != Readiness::Starting) => {}
+        _ = tokio::time::sleep(check.timeout()) => {
+            let timed_out = ready.send_if_modified(|state| {
+                let starting = *state == Readiness::Starting;
+                if starting {
+                    *state = Readiness::Failed;
+                }
+                starting
+            });
+            if timed_out {
+                output.line(
+                    &style(format!("Not ready after {}s, apps depending on it will not start", check.timeout().as_secs()))
+                        .red()
+                        .to_string(),
+                );
+            }
+        }
+    }
+}
+
+fn mark_ready(ready: &watch::Sender<Readiness>, output: &TaskOutput) {
+    let became_ready = ready.send_if_modified(|state| {
+        let starting = *state == Readiness::Starting;
+        if starting {
+            *state = Readiness::Ready;
+        }
+        starting
+    });
+    if became_ready {
+        output.line(&style("✅ Ready").green().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_start_order_follows_dev_depends_on() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "knot.yml", "name: demo\n");
+        write(root, "apps/api/app.yml", "name: api\nscripts:\n  dev: cargo run\ndev:\n  ready:\n    port: 8000\n");
+        write(
+            root,
+            "apps/web/app.yml",
+            "name: web\nscripts:\n  dev: npm run dev\ndev:\n  depends_on: [api]\n  restart: true\n",
+        );
+        write(root, "apps/docs/app.yml", "name: docs\n");
+
+        let project = Project::find_and_load(root).unwrap();
+        assert_eq!(start_order(&project, &["web".to_string()]).unwrap(), vec!["api", "web"]);
+
+        let error = start_order(&project, &["docs".to_string()]).unwrap_err().to_string();
+        assert!(error.contains("has no 'dev' script"));
+    }
+
+    #[test]
+    fn test_restart_delay_doubles() {
+        assert_eq!(restart_delay(0), RESTART_DELAY);
+        assert_eq!(restart_delay(1), RESTART_DELAY * 2);
+        assert_eq!(restart_delay(MAX_RESTARTS - 1), Duration::from_secs(16));
+    }
+}
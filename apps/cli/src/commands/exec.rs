@@ -0,0 +1,92 @@
+use anyhow::Result;
+use console::style;
+
+use crate::commands::common::display_info;
+use crate::config::Script;
+use crate::env;
+use crate::filter::Filter;
+use crate::graph::ProjectGraph;
+use crate::project::Project;
+use crate::tasks::{self, scheduler::{self, SchedulerOptions}, summary, Task};
+
+/// Name of the tasks in prefixed output, reports and dependency ordering
+const EXEC_SCRIPT: &str = "exec";
+
+/// Run an ad-hoc command in every app and package selected by `filter` (all when empty), in
+/// parallel with prefixed output and a summary. Packages run before the projects depending
+/// on them, like `knot run --all`.
+pub async fn exec_command(command: &[String], filter: &Filter, options: &SchedulerOptions) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let graph = ProjectGraph::from_project(&project);
+
+    let command = command_line(command);
+    if command.trim().is_empty() {
+        anyhow::bail!("No command given\n💡 Pass the command after --, e.g. knot exec -- rm -rf dist");
+    }
+
+    let nodes = graph.topological_order(&filter.select(&project, &graph)?)?;
+    if nodes.is_empty() {
+        display_info("No app or package matches the filter");
+        return Ok(());
+    }
+
+    let script = Script::Command(command.clone());
+    let tasks = nodes
+        .into_iter()
+        .map(|node| {
+            let dir = node.dir(&project.root);
+            let mut task = Task::new(node.clone(), EXEC_SCRIPT, &command, dir.clone());
+            task.env = env::script_env(Some(&project), Some(&node), &dir, &script)?;
+            Ok(task)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    println!(
+        "🚀 Running '{}' in {} project(s) ({} at a time)...",
+        style(&command).cyan(),
+        tasks.len(),
+        options.concurrency
+    );
+
+    let results = scheduler::run_tasks(&tasks, &graph, None, options).await?;
+    summary::print_summary(&results);
+
+    let failed = results.iter().filter(|result| !result.is_success()).count();
+    if failed > 0 {
+        return Err(tasks::ExitError {
+            code: tasks::worst_exit_code(&results),
+            message: format!(
+                "Command '{}' did not succeed in {} of {} project(s)\n💡 Scroll up to the prefixed output of the failed projects for details",
+                command,
+                failed,
+                results.len()
+            ),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// A single argument is run as written (`knot exec -- "tsc && vite build"`), several are
+/// shell-escaped and joined
+fn command_line(command: &[String]) -> String {
+    match command {
+        [single] => single.clone(),
+        args => args.iter().map(|arg| tasks::shell_escape(arg, None)).collect::<Vec<_>>().join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(command_line(&args(&["rm -rf dist && echo done"])), "rm -rf dist && echo done");
+        assert_eq!(command_line(&args(&["npx", "tsc", "--noEmit"])), "npx tsc --noEmit");
+        assert_eq!(command_line(&args(&["echo", "it's here"])), "echo 'it'\\''s here'");
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use console::style;
+use serde_json::json;
+
+use crate::commands::common::display_info;
+use crate::filter::Filter;
+use crate::graph::ProjectGraph;
+use crate::project::Project;
+
+/// List the apps and packages selected by `filter` (all when empty) with the local packages
+/// each one depends on, packages first, as text or JSON
+pub fn show_graph(filter: &Filter, as_json: bool) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+    let graph = ProjectGraph::from_project(&project);
+
+    let nodes = graph.topological_order(&filter.select(&project, &graph)?)?;
+
+    if as_json {
+        let nodes: Vec<_> = nodes
+            .iter()
+            .map(|node| {
+                let dependencies: Vec<&str> = graph.dependencies_of(node).map(|dep| dep.name()).collect();
+                json!({
+                    "name": node.name(),
+                    "kind": node.kind(),
+                    "path": node.dir(std::path::Path::new("")).to_string_lossy(),
+                    "dependencies": dependencies,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json!({ "nodes": nodes }))?);
+        return Ok(());
+    }
+
+    if nodes.is_empty() {
+        display_info("No app or package matches the filter");
+        return Ok(());
+    }
+
+    println!("🕸️  Project graph ({}):", nodes.len());
+    for node in &nodes {
+        let icon = if node.kind() == "app" { "📱" } else { "📦" };
+        println!("  {} {} {}", icon, node.name(), style(format!("({})", node.kind())).dim());
+        let mut dependencies = graph.dependencies_of(node).peekable();
+        while let Some(dependency) = dependencies.next() {
+            let branch = if dependencies.peek().is_some() { "├──" } else { "└──" };
+            println!("     {} {}", style(branch).dim(), dependency.name());
+        }
+    }
+    Ok(())
+}
@@ -153,6 +153,9 @@ pub fn init_project(name: Option<&str>, path: Option<&str>, description: Option<
         apps: None,
         scripts: None,
         variables: None,
+        cache: None,
+        remote_cache: None,
+        tools: None,
     };
 
     let yaml_content = serde_yaml::to_string(&config)?;
@@ -644,8 +647,11 @@ pub async fn init_app(name: Option<&str>, template: Option<&str>, description: O
         description: app_description.clone(),
         ts_alias: None,
         packages: None,
+        build: None,
         scripts: None,
+        dev: None,
         variables: None,
+        tools: None,
     };
 
     let yaml_content = serde_yaml::to_string(&config)?;
@@ -1,5 +1,11 @@
+pub mod affected;
+pub mod build;
+pub mod cache;
 pub mod common;
 pub mod deps;
+pub mod dev;
+pub mod exec;
+pub mod graph;
 pub mod init;
 pub mod package;
 pub mod publish;
@@ -7,15 +13,23 @@ pub mod run;
 pub mod system;
 pub mod team;
 pub mod variables;
+pub mod vendor;
 pub mod version;
 
 // Re-export functions from each module
+pub use affected::show_affected;
+pub use build::build_apps;
+pub use cache::{cache_stats, cache_clear, cache_clean, cache_gc, cache_invalidate};
+pub use dev::dev_apps;
+pub use exec::exec_command;
+pub use graph::show_graph;
 pub use deps::{deps_add, deps_list, deps_resolve, deps_check, deps_tree, deps_outdated, deps_why, deps_sync};
 pub use init::{init_project, init_package, init_app};
 pub use package::{link_packages, add_package};
 pub use publish::{publish_package, delete_package};
-pub use run::{run_script, run_script_interactive};
-pub use system::{show_status, auth_status, update_cli};
+pub use run::{run_script, run_script_all, run_script_interactive};
+pub use system::{show_status, doctor, auth_status, update_cli};
 pub use team::{create_team, list_teams, team_info, add_team_member, remove_team_member};
 pub use variables::{vars_list, vars_get};
+pub use vendor::vendor_packages;
 pub use version::{version_bump, version_prerelease, version_set};
\ No newline at end of file
@@ -1,14 +1,18 @@
 use anyhow::{Context, Result};
 use std::fs;
 
+use crate::commands::run::run_project_hook;
 use crate::commands::common::{create_spinner, finish_progress, fail_progress, display_success, display_error, display_info};
 use crate::config::AppConfig;
+use crate::filter::Filter;
+use crate::graph::{ProjectGraph, ProjectNode};
 use crate::linker::Linker;
 use crate::project::Project;
 use crate::typescript::TypeScriptManager;
 use crate::validation::{validate_package_spec, sanitize_input};
 
-pub async fn link_packages(use_symlinks: bool) -> Result<()> {
+/// Link packages into every app, or into the apps selected by `filter`
+pub async fn link_packages(use_symlinks: bool, filter: &Filter) -> Result<()> {
     let start_time = std::time::Instant::now();
 
     let current_dir = std::env::current_dir()?;
@@ -23,11 +27,31 @@ pub async fn link_packages(use_symlinks: bool) -> Result<()> {
         }
     };
 
+    let app_names: Vec<String> = if filter.is_empty() {
+        project.get_app_names()
+    } else {
+        let graph = ProjectGraph::from_project(&project);
+        filter
+            .select(&project, &graph)?
+            .into_iter()
+            .filter_map(|node| match node {
+                ProjectNode::App(app_name) => Some(app_name),
+                ProjectNode::Package(_) => None,
+            })
+            .collect()
+    };
+    if app_names.is_empty() {
+        display_info("No app matches the filter, nothing to link");
+        return Ok(());
+    }
+
+    run_project_hook(&project, "prelink").await?;
+
     let mode = if use_symlinks { "symlinked" } else { "copied" };
     let spinner = create_spinner(&format!("Linking packages ({} mode)...", mode));
 
     let linker = Linker::new(&project);
-    match linker.link_all_apps(use_symlinks).await {
+    match linker.link_apps(&app_names, use_symlinks).await {
         Ok(_) => {}
         Err(e) => {
             fail_progress(&spinner, "Failed to link packages");
@@ -38,7 +62,7 @@ pub async fn link_packages(use_symlinks: bool) -> Result<()> {
     spinner.set_message("Setting up TypeScript aliases...".to_string());
 
     let ts_manager = TypeScriptManager::new(&project);
-    match ts_manager.setup_aliases_for_all_apps() {
+    match ts_manager.setup_aliases_for_apps(&app_names) {
         Ok(_) => {}
         Err(e) => {
             fail_progress(&spinner, "Failed to setup TypeScript aliases");
@@ -50,6 +74,8 @@ pub async fn link_packages(use_symlinks: bool) -> Result<()> {
     finish_progress(&spinner, &format!("All packages {} and TypeScript configured", mode));
     display_success(&format!("Successfully {} all packages and updated TypeScript configurations", mode));
     display_info(&format!("Completed in {}ms", duration.as_millis()));
+
+    run_project_hook(&project, "postlink").await?;
     Ok(())
 }
 
@@ -171,7 +197,7 @@ pub async fn add_package(package_spec: &str, auto_link: bool) -> Result<()> {
     // Auto-link if requested
     if auto_link {
         println!("🔗 Linking packages...");
-        link_packages(false).await?;
+        link_packages(false, &Filter::default()).await?;
     } else {
         println!("💡 Run 'knot link' to apply the changes");
     }
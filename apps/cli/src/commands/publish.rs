@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use crate::commands::run::run_package_hook;
 use crate::config::PackageConfig;
 use crate::ignore::KnotIgnore;
 use crate::utils;
@@ -290,6 +291,9 @@ pub async fn publish_package(team: Option<&str>, description: Option<&str>) -> R
         println!("💡 Continuing with publish attempt...");
     }
 
+    // Run the prepublish hook before packing, e.g. to build the package
+    run_package_hook(&current_dir, "prepublish").await?;
+
     // Create package tarball
     let tarball_path = format!("{}-{}.tar.gz", package_config.name, package_config.version);
     create_package_tarball(&package_config.name, &tarball_path)?;
@@ -354,6 +358,7 @@ pub async fn publish_package(team: Option<&str>, description: Option<&str>) -> R
         if let Some(team_name) = &metadata.team_name {
             println!("   Team: {}", team_name);
         }
+        run_package_hook(&current_dir, "postpublish").await?;
     } else {
         let status = upload_response.status();
         let text = upload_response.text().await.unwrap_or_default();
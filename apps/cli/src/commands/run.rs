@@ -1,20 +1,74 @@
-use crate::commands::common::{create_spinner, display_error, display_info, display_success};
-use crate::config::{AppConfig, PackageConfig};
+use crate::affected;
+use crate::commands::common::{create_spinner, display_error, display_info, display_success, display_tip};
+use crate::config::{AppConfig, PackageConfig, Script};
+use crate::env;
+use crate::filter::Filter;
 use crate::project::Project;
+use crate::graph::{ProjectGraph, ProjectNode};
+use crate::toolchain;
+use crate::tasks::{self, process, scheduler::{self, SchedulerOptions}, summary, TaskStatus};
 use crate::utils;
+use crate::watch::{self, ChangeWatcher};
 use crate::validation::{validate_script_name, sanitize_input};
 use anyhow::{Context, Result};
 use console::style;
 use inquire::Select;
+use std::collections::{BTreeSet, HashMap};
 use std::io::IsTerminal;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
 
 // Check if running in interactive environment
 fn is_interactive() -> bool {
     std::io::stdin().is_terminal()
 }
 
-pub async fn run_script(script_name: &str) -> Result<()> {
+/// Scripts of one config file and where they run
+struct ScriptSource {
+    scripts: HashMap<String, Script>,
+    dir: PathBuf,
+    context: &'static str,
+    /// App or package owning the scripts, needed for `^script` dependencies
+    node: Option<ProjectNode>,
+}
+
+impl ScriptSource {
+    fn app(config: AppConfig, dir: &Path) -> Self {
+        Self {
+            scripts: config.scripts.unwrap_or_default(),
+            dir: dir.to_path_buf(),
+            context: "app",
+            node: dir_name(dir).map(ProjectNode::App),
+        }
+    }
+
+    fn package(config: PackageConfig, dir: &Path) -> Self {
+        Self {
+            scripts: config.scripts.unwrap_or_default(),
+            dir: dir.to_path_buf(),
+            context: "package",
+            node: dir_name(dir).map(ProjectNode::Package),
+        }
+    }
+
+    fn project(project: &Project) -> Self {
+        Self {
+            scripts: project.config.scripts.clone().unwrap_or_default(),
+            dir: project.root.clone(),
+            context: "project",
+            node: None,
+        }
+    }
+}
+
+fn dir_name(dir: &Path) -> Option<String> {
+    dir.file_name().map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Run a script of the app, package or project in the current directory, passing `args`
+/// through to its command. With `watch`, the script is re-run whenever its files change; with
+/// `explain_env`, only the environment it would run with is printed.
+pub async fn run_script(script_name: &str, args: &[String], watch: bool, explain_env: bool) -> Result<()> {
     let current_dir = std::env::current_dir()?;
 
     // Validate script name
@@ -26,15 +80,23 @@ pub async fn run_script(script_name: &str) -> Result<()> {
 
     // Check if we're in an app directory (has app.yml or app.yaml)
     if let Some(app_config_path) = utils::find_yaml_file(&current_dir, "app") {
-        if let Some(script_command) = find_script_in_app(&app_config_path, script_name)? {
-            return execute_script(script_name, &script_command, &current_dir, "app").await;
+        let source = ScriptSource::app(AppConfig::from_file(&app_config_path)?, &current_dir);
+        if source.scripts.contains_key(script_name) {
+            if explain_env {
+                return explain_script_env(script_name, &source);
+            }
+            return run_or_watch(script_name, args, &source, watch).await;
         }
     }
 
     // Check if we're in a package directory (has package.yml or package.yaml)
     if let Some(package_config_path) = utils::find_yaml_file(&current_dir, "package") {
-        if let Some(script_command) = find_script_in_package(&package_config_path, script_name)? {
-            return execute_script(script_name, &script_command, &current_dir, "package").await;
+        let source = ScriptSource::package(PackageConfig::from_file(&package_config_path)?, &current_dir);
+        if source.scripts.contains_key(script_name) {
+            if explain_env {
+                return explain_script_env(script_name, &source);
+            }
+            return run_or_watch(script_name, args, &source, watch).await;
         }
     }
 
@@ -49,10 +111,12 @@ pub async fn run_script(script_name: &str) -> Result<()> {
     };
 
     // Check knot.yml for scripts
-    if let Some(scripts) = &project.config.scripts {
-        if let Some(script_command) = scripts.get(script_name) {
-            return execute_script(script_name, script_command, &project.root, "project").await;
+    let source = ScriptSource::project(&project);
+    if source.scripts.contains_key(script_name) {
+        if explain_env {
+            return explain_script_env(script_name, &source);
         }
+        return run_or_watch(script_name, args, &source, watch).await;
     }
 
     // If script not found anywhere, show available scripts
@@ -63,6 +127,103 @@ pub async fn run_script(script_name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Run a script in every app and package that defines it, then print a summary. With `since`,
+/// only apps and packages affected by the changes since that git ref are included, and a
+/// non-empty `filter` keeps only the apps and packages it selects; the scripts they depend on
+/// run wherever they are defined.
+pub async fn run_script_all(
+    script_name: &str,
+    args: &[String],
+    since: Option<&str>,
+    filter: &Filter,
+    options: &SchedulerOptions,
+) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+
+    let sanitized_script_name = sanitize_input(script_name);
+    validate_script_name(&sanitized_script_name)?;
+
+    let graph = ProjectGraph::from_project(&project);
+    // The apps and packages whose script was asked for; the scripts they depend on run too,
+    // even where they are not selected
+    let mut roots: BTreeSet<ProjectNode> = graph
+        .all_nodes()
+        .into_iter()
+        .filter(|node| tasks::node_script(&project, node, &sanitized_script_name).is_some())
+        .collect();
+    if roots.is_empty() {
+        display_error(&format!("No app or package defines a '{}' script", sanitized_script_name));
+        display_info("Add it to the scripts section of an app.yml or package.yml");
+        anyhow::bail!("Script '{}' not found in any app or package", sanitized_script_name);
+    }
+    if let Some(since) = since {
+        let changed_files = affected::changed_files(&project.root, since)?;
+        let affected = affected::affected_nodes(&project, &graph, &changed_files);
+        let total = roots.len();
+        roots.retain(|node| affected.contains(node));
+
+        println!(
+            "🔍 {} changed file(s) since '{}' affect {} of {} project(s) defining '{}'",
+            changed_files.len(),
+            style(since).cyan(),
+            roots.len(),
+            total,
+            sanitized_script_name
+        );
+        if roots.is_empty() {
+            display_success("Nothing to run");
+            return Ok(());
+        }
+    }
+    if !filter.is_empty() {
+        let selected = filter.select(&project, &graph)?;
+        roots.retain(|node| selected.contains(node));
+        if roots.is_empty() {
+            display_info(&format!("No selected app or package defines a '{}' script", sanitized_script_name));
+            return Ok(());
+        }
+    }
+    let tasks = tasks::collect_script_tasks(&project, &graph, &roots, &sanitized_script_name, args)?;
+
+    // A project without apps among the tasks still checks the tools of knot.yml
+    let apps: BTreeSet<&str> = tasks
+        .iter()
+        .filter_map(|task| match &task.node {
+            ProjectNode::App(app) => Some(app.as_str()),
+            ProjectNode::Package(_) => None,
+        })
+        .collect();
+    toolchain::ensure(&project, &apps.into_iter().collect::<Vec<_>>())?;
+
+    println!(
+        "🚀 Running '{}' as {} task(s) ({} at a time)...",
+        style(&sanitized_script_name).cyan(),
+        tasks.len(),
+        options.concurrency
+    );
+
+    let cache = options.use_cache.then(|| project.task_cache()).transpose()?;
+    let results = scheduler::run_tasks(&tasks, &graph, cache.as_ref(), options).await?;
+    summary::print_summary(&results);
+
+    let failed = results.iter().filter(|result| !result.is_success()).count();
+    if failed > 0 {
+        return Err(tasks::ExitError {
+            code: tasks::worst_exit_code(&results),
+            message: format!(
+                "Script '{}' did not succeed in {} of {} project(s)\n💡 Scroll up to the prefixed output of the failed projects for details",
+                sanitized_script_name,
+                failed,
+                results.len()
+            ),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
 pub async fn run_script_interactive() -> Result<()> {
     let current_dir = std::env::current_dir()?;
     
@@ -73,56 +234,43 @@ pub async fn run_script_interactive() -> Result<()> {
         return Ok(());
     }
 
-    // Collect all available scripts
-    let mut all_scripts = Vec::new();
-
     // First check local directories for app/package configs, then try to load project
     // This follows the same priority as non-interactive mode
     let project = Project::find_and_load(&current_dir).ok();
+    let mut sources = Vec::new();
 
     // Check if we're in an app directory (has app.yml or app.yaml)
     if let Some(app_config_path) = utils::find_yaml_file(&current_dir, "app") {
         if let Ok(app_config) = AppConfig::from_file(&app_config_path) {
-            if let Some(scripts) = &app_config.scripts {
-                for (name, command) in scripts {
-                    all_scripts.push((
-                        format!("{} {}", name, style(format!("({})", app_config.name)).dim()),
-                        name.clone(),
-                        command.clone(),
-                        "app".to_string(),
-                    ));
-                }
-            }
+            let owner = app_config.name.clone();
+            sources.push((owner, ScriptSource::app(app_config, &current_dir)));
         }
     }
 
     // Check if we're in a package directory (has package.yml or package.yaml)
     if let Some(package_config_path) = utils::find_yaml_file(&current_dir, "package") {
         if let Ok(package_config) = PackageConfig::from_file(&package_config_path) {
-            if let Some(scripts) = &package_config.scripts {
-                for (name, command) in scripts {
-                    all_scripts.push((
-                        format!("{} {}", name, style(format!("({})", package_config.name)).dim()),
-                        name.clone(),
-                        command.clone(),
-                        "package".to_string(),
-                    ));
-                }
-            }
+            let owner = package_config.name.clone();
+            sources.push((owner, ScriptSource::package(package_config, &current_dir)));
         }
     }
 
     // Add project scripts
     if let Some(project) = &project {
-        if let Some(scripts) = &project.config.scripts {
-            for (name, command) in scripts {
-                all_scripts.push((
-                    format!("{} {}", name, style(format!("({})", project.config.name)).dim()),
-                    name.clone(),
-                    command.clone(),
-                    "project".to_string(),
-                ));
-            }
+        sources.push((project.config.name.clone(), ScriptSource::project(project)));
+    }
+
+    // Collect all available scripts as (label, script name, source index)
+    let mut all_scripts = Vec::new();
+    for (index, (owner, source)) in sources.iter().enumerate() {
+        let mut names: Vec<&String> = source.scripts.keys().collect();
+        names.sort();
+        for name in names {
+            let label = match source.scripts[name].description() {
+                Some(description) => format!("{} - {} {}", name, description, style(format!("({})", owner)).dim()),
+                None => format!("{} {}", name, style(format!("({})", owner)).dim()),
+            };
+            all_scripts.push((label, name.clone(), index));
         }
     }
 
@@ -152,7 +300,7 @@ pub async fn run_script_interactive() -> Result<()> {
     
     let script_options: Vec<String> = all_scripts
         .iter()
-        .map(|(display_name, _, _, _)| display_name.clone())
+        .map(|(display_name, _, _)| display_name.clone())
         .collect();
 
     let selection = Select::new("Select a script:", script_options.clone())
@@ -166,25 +314,15 @@ pub async fn run_script_interactive() -> Result<()> {
             let selected_index = script_options.iter()
                 .position(|opt| opt == &selected_text)
                 .unwrap_or(0);
-            let selected_script = &all_scripts[selected_index];
-            let (_, script_name, script_command, context) = selected_script;
+            let (_, script_name, source_index) = &all_scripts[selected_index];
+            let source = &sources[*source_index].1;
             
             println!("✨ Running script: {}", style(script_name).green().bold());
-            println!("📍 Command: {}", style(script_command).dim());
-            println!("🔧 Context: {}", context);
+            println!("📍 Command: {}", style(&source.scripts[script_name]).dim());
+            println!("🔧 Context: {}", source.context);
             println!();
 
-            let working_dir = if let Some(ref proj) = project {
-                if context == "project" {
-                    &proj.root
-                } else {
-                    &current_dir
-                }
-            } else {
-                &current_dir
-            };
-
-            execute_script(script_name, script_command, working_dir, context).await?;
+            run_with_dependencies(script_name, &[], source).await?;
         }
         Err(inquire::InquireError::OperationCanceled) => {
             display_info("Script selection cancelled");
@@ -197,30 +335,399 @@ pub async fn run_script_interactive() -> Result<()> {
     Ok(())
 }
 
-fn find_script_in_app(app_yml_path: &Path, script_name: &str) -> Result<Option<String>> {
-    let app_config = AppConfig::from_file(app_yml_path)?;
-    Ok(app_config
-        .scripts
-        .as_ref()
-        .and_then(|scripts| scripts.get(script_name))
-        .cloned())
+/// Run a script after the scripts it `depends_on`: same-level scripts run in order first,
+/// `^script` dependencies run in the app's or package's local dependencies. `pre<name>` and
+/// `post<name>` hooks run around each script unless scripts are ignored. Only the requested
+/// script receives `args`.
+async fn run_with_dependencies(script_name: &str, args: &[String], source: &ScriptSource) -> Result<()> {
+    check_tools(source)?;
+    run_scripts(script_name, args, source, !utils::ignore_scripts()).await
+}
+
+async fn run_scripts(script_name: &str, args: &[String], source: &ScriptSource, hooks: bool) -> Result<()> {
+    let mut order = Vec::new();
+    script_order(script_name, source, hooks, &mut Vec::new(), &mut order)?;
+
+    for name in order {
+        let script_args = if name == script_name { args } else { &[] };
+        run_step(name, script_args, source).await?;
+    }
+
+    Ok(())
+}
+
+/// Run one script of `source` after its `^script` dependencies
+async fn run_step(name: &str, args: &[String], source: &ScriptSource) -> Result<()> {
+    let script = &source.scripts[name];
+    for upstream in script.depends_on().iter().filter_map(|dep| dep.strip_prefix('^')) {
+        run_upstream_script(name, upstream, source).await?;
+    }
+    execute_script(name, script, args, source).await
+}
+
+/// Check the `tools` of the project, and of the app running the scripts, before they run
+fn check_tools(source: &ScriptSource) -> Result<()> {
+    let Ok(project) = Project::find_and_load(&source.dir) else {
+        return Ok(());
+    };
+    match &source.node {
+        Some(ProjectNode::App(app)) => toolchain::ensure(&project, &[app.as_str()]),
+        _ => toolchain::ensure(&project, &[]),
+    }
+}
+
+/// Longest value printed by `--explain-env`
+const EXPLAINED_VALUE_LENGTH: usize = 60;
+
+/// Print every variable the script would see and where it comes from, without running it
+fn explain_script_env(script_name: &str, source: &ScriptSource) -> Result<()> {
+    let script = &source.scripts[script_name];
+    let project = Project::find_and_load(&source.dir).ok();
+    let env = env::explain_env(project.as_ref(), source.node.as_ref(), &source.dir, script)?;
+
+    match script.env_passthrough() {
+        Some(allowed) => println!(
+            "🔒 Script '{}' runs in a cleared environment; env_passthrough: [{}]",
+            script_name,
+            allowed.join(", ")
+        ),
+        None => println!(
+            "📋 Script '{}' inherits knot's environment (no env_passthrough)",
+            script_name
+        ),
+    }
+    println!();
+
+    let rows: Vec<(&String, String, &String)> = env
+        .iter()
+        .map(|(key, (value, origin))| {
+            let origin = match origin {
+                env::EnvOrigin::Inherited => "inherited".to_string(),
+                env::EnvOrigin::Passthrough => "env_passthrough".to_string(),
+                env::EnvOrigin::Injected => "knot".to_string(),
+                env::EnvOrigin::File(path) => {
+                    let root = project.as_ref().map_or(source.dir.as_path(), |project| project.root.as_path());
+                    path.strip_prefix(root).unwrap_or(path).display().to_string()
+                }
+                env::EnvOrigin::Script => "script env".to_string(),
+            };
+            (key, origin, value)
+        })
+        .collect();
+    let key_width = rows.iter().map(|(key, _, _)| key.len()).max().unwrap_or(0);
+    let origin_width = rows.iter().map(|(_, origin, _)| origin.len()).max().unwrap_or(0);
+    for (key, origin, value) in rows {
+        println!(
+            "  {:key_width$}  {}  {}",
+            key,
+            style(format!("{:origin_width$}", origin, origin_width = origin_width)).dim(),
+            console::truncate_str(value, EXPLAINED_VALUE_LENGTH, "…"),
+            key_width = key_width
+        );
+    }
+
+    if script.env_passthrough().is_some() {
+        println!();
+        display_info("Variables let through by env_passthrough count toward the task cache key");
+    }
+    Ok(())
+}
+
+async fn run_or_watch(script_name: &str, args: &[String], source: &ScriptSource, watch: bool) -> Result<()> {
+    if watch {
+        watch_script(script_name, args, source).await
+    } else {
+        run_with_dependencies(script_name, args, source).await
+    }
+}
+
+/// How a watched script's run ended
+enum WatchedRun {
+    Exited(Result<std::process::ExitStatus>),
+    Changed(Vec<PathBuf>),
+}
+
+/// Run a script, then run it again whenever a file of its app or package, or of a local
+/// package it depends on, changes. A script still running (e.g. a dev server) is stopped
+/// along with its process tree first. Failures are reported and watching goes on.
+async fn watch_script(script_name: &str, args: &[String], source: &ScriptSource) -> Result<()> {
+    check_tools(source)?;
+    let mut order = Vec::new();
+    script_order(script_name, source, !utils::ignore_scripts(), &mut Vec::new(), &mut order)?;
+    let position = order.iter().position(|name| *name == script_name).unwrap_or_default();
+    let (before, after) = (&order[..position], &order[position + 1..]);
+
+    let dirs = match (&source.node, Project::find_and_load(&source.dir)) {
+        (Some(node), Ok(project)) => watch::watch_dirs(&project, &ProjectGraph::from_project(&project), node),
+        _ => vec![source.dir.clone()],
+    };
+    let script = &source.scripts[script_name];
+    let script_dir = script.cwd().map_or_else(|| source.dir.clone(), |cwd| source.dir.join(cwd));
+    let outputs = script.outputs().iter().map(|output| script_dir.join(output)).collect();
+    let mut watcher = ChangeWatcher::new(&dirs)?.with_excluded(outputs);
+
+    println!("👀 Watching {} director{} for changes (Ctrl+C to stop):", dirs.len(), if dirs.len() == 1 { "y" } else { "ies" });
+    for dir in &dirs {
+        println!("   {}", style(dir.display()).dim());
+    }
+
+    loop {
+        let changed = match run_watched(script_name, args, source, before, after, &mut watcher).await {
+            Ok(Some(changed)) => changed,
+            Ok(None) => watcher.next_change().await,
+            Err(e) => {
+                display_error(&format!("{:#}", e));
+                watcher.next_change().await
+            }
+        };
+
+        let first = changed.first().map(|path| path.display().to_string()).unwrap_or_default();
+        let more = match changed.len() {
+            0 | 1 => String::new(),
+            n => format!(" and {} more", n - 1),
+        };
+        println!("\n🔄 {}{} changed, re-running '{}'...", style(first).cyan(), more, script_name);
+    }
+}
+
+/// One watch cycle: the scripts `before` the watched one and its `^` upstream scripts, the
+/// script itself until it exits or a file changes, then the scripts `after` it (post hooks).
+/// Returns the changed files when a change cut the script short.
+async fn run_watched(
+    script_name: &str,
+    args: &[String],
+    source: &ScriptSource,
+    before: &[&str],
+    after: &[&str],
+    watcher: &mut ChangeWatcher,
+) -> Result<Option<Vec<PathBuf>>> {
+    for name in before {
+        run_step(name, &[], source).await?;
+    }
+
+    let script = &source.scripts[script_name];
+    for upstream in script.depends_on().iter().filter_map(|dep| dep.strip_prefix('^')) {
+        run_upstream_script(script_name, upstream, source).await?;
+    }
+    let (mut cmd, script_command) = prepare_script(script_name, script, args, source)?;
+    cmd.stdin(std::process::Stdio::null());
+    process::isolate(&mut cmd);
+
+    watcher.clear();
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to execute script '{}': {}", script_name, script_command))?;
+    let pid = child.id();
+    if let Some(pid) = pid {
+        process::register(pid);
+    }
+
+    let end = tokio::select! {
+        status = wait_for_script(script_name, script, &mut child) => WatchedRun::Exited(status),
+        changed = watcher.next_change() => WatchedRun::Changed(changed),
+    };
+    if let Some(pid) = pid {
+        if matches!(end, WatchedRun::Changed(_)) {
+            stop_script_tree(pid, &mut child).await;
+        }
+        process::unregister(pid);
+    }
+    process::halt_if_stopping().await;
+
+    match end {
+        WatchedRun::Changed(changed) => Ok(Some(changed)),
+        WatchedRun::Exited(status) => {
+            check_status(script_name, &script_command, status?)?;
+            display_success(&format!("Script '{}' completed successfully", script_name));
+            for name in after {
+                run_step(name, &[], source).await?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Ask the script's process group to terminate, killing the script if it does not exit in time
+async fn stop_script_tree(pid: u32, child: &mut tokio::process::Child) {
+    process::terminate(pid);
+    if tokio::time::timeout(process::GRACE_PERIOD, child.wait()).await.is_err() {
+        let _ = child.kill().await;
+    }
+}
+
+/// Scripts of `source` to run for `name`, dependencies first, with its hooks if `hooks` is set
+fn script_order<'a>(
+    name: &'a str,
+    source: &'a ScriptSource,
+    hooks: bool,
+    visiting: &mut Vec<&'a str>,
+    order: &mut Vec<&'a str>,
+) -> Result<()> {
+    if order.contains(&name) {
+        return Ok(());
+    }
+    if visiting.contains(&name) {
+        visiting.push(name);
+        anyhow::bail!(
+            "Circular depends_on between scripts: {}\n💡 Remove one of the depends_on entries",
+            visiting.join(" → ")
+        );
+    }
+
+    let script = source.scripts.get(name).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Script '{}' depends on '{}', which is not defined in the {} config\n💡 Add a '{}' script or remove it from depends_on",
+            visiting.last().unwrap_or(&name), name, source.context, name
+        )
+    })?;
+
+    visiting.push(name);
+    for dependency in script.depends_on().iter().filter(|dep| !dep.starts_with('^')) {
+        script_order(dependency, source, hooks, visiting, order)?;
+    }
+    if let Some((pre, _)) = source.scripts.get_key_value(&tasks::pre_hook(name)).filter(|_| hooks) {
+        script_order(pre, source, false, visiting, order)?;
+    }
+    visiting.pop();
+    order.push(name);
+    if let Some((post, _)) = source.scripts.get_key_value(&tasks::post_hook(name)).filter(|_| hooks) {
+        script_order(post, source, false, visiting, order)?;
+    }
+    Ok(())
+}
+
+/// Run the `hook` script of the project's knot.yml (e.g. `postlink`), if it defines one
+pub async fn run_project_hook(project: &Project, hook: &str) -> Result<()> {
+    run_hook(hook, &ScriptSource::project(project)).await
 }
 
-fn find_script_in_package(package_yml_path: &Path, script_name: &str) -> Result<Option<String>> {
-    let package_config = PackageConfig::from_file(package_yml_path)?;
-    Ok(package_config
-        .scripts
-        .as_ref()
-        .and_then(|scripts| scripts.get(script_name))
-        .cloned())
+/// Run the `hook` script of the package.yml in `dir` (e.g. `prepublish`), if it defines one
+pub async fn run_package_hook(dir: &Path, hook: &str) -> Result<()> {
+    let Some(config_path) = utils::find_yaml_file(dir, "package") else {
+        return Ok(());
+    };
+    run_hook(hook, &ScriptSource::package(PackageConfig::from_file(&config_path)?, dir)).await
 }
 
+async fn run_hook(hook: &str, source: &ScriptSource) -> Result<()> {
+    if utils::ignore_scripts() || !source.scripts.contains_key(hook) {
+        return Ok(());
+    }
+    run_scripts(hook, &[], source, false).await
+}
+
+/// Run `script` in every local package the source's app or package depends on
+async fn run_upstream_script(script_name: &str, upstream: &str, source: &ScriptSource) -> Result<()> {
+    let Some(node) = &source.node else {
+        anyhow::bail!(
+            "Script '{}' depends on '^{}', but {} scripts have no package dependencies\n💡 Use '^{}' in app.yml or package.yml scripts",
+            script_name, upstream, source.context, upstream
+        );
+    };
+
+    let project = Project::find_and_load(&source.dir)?;
+    let graph = ProjectGraph::from_project(&project);
+    let tasks = tasks::collect_upstream_tasks(&project, &graph, node, upstream)?;
+    if tasks.is_empty() {
+        return Ok(());
+    }
+
+    println!("🔗 Running '{}' in {} dependenc{} first...", upstream, tasks.len(), if tasks.len() == 1 { "y" } else { "ies" });
+    let options = SchedulerOptions::default();
+    let cache = options.use_cache.then(|| project.task_cache()).transpose()?;
+    let results = scheduler::run_tasks(&tasks, &graph, cache.as_ref(), &options).await?;
+
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|result| !result.is_success())
+        .map(|result| result.task.label())
+        .collect();
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "Script '{}' was not run because '{}' failed in: {}\n💡 Check the prefixed output above for details",
+            script_name, upstream, failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Run the script, retrying up to its `retries` times after it fails or times out
 async fn execute_script(
     script_name: &str,
-    script_command: &str,
-    working_dir: &Path,
-    context: &str,
+    script: &Script,
+    args: &[String],
+    source: &ScriptSource,
 ) -> Result<()> {
+    let attempts = script.retries() + 1;
+    for attempt in 1..=attempts {
+        if attempt > 1 {
+            println!("🔁 Retrying script '{}', attempt {} of {}", script_name, attempt, attempts);
+        }
+
+        let (mut cmd, script_command) = prepare_script(script_name, script, args, source)?;
+        cmd.stdin(std::process::Stdio::inherit());
+        // A script reading from the terminal stays in its process group, getting Ctrl-C from it
+        let interactive = std::io::stdin().is_terminal();
+        if interactive {
+            cmd.kill_on_drop(true);
+        } else {
+            process::isolate(&mut cmd);
+        }
+
+        let mut child = cmd.spawn().with_context(|| {
+            format!(
+                "Failed to execute script '{}': {}",
+                script_name, script_command
+            )
+        })?;
+        let pid = child.id();
+        match pid {
+            Some(pid) if interactive => process::register_foreground(pid),
+            Some(pid) => process::register(pid),
+            None => {}
+        }
+
+        let result = wait_for_script(script_name, script, &mut child)
+            .await
+            .and_then(|status| check_status(script_name, &script_command, status));
+        if let Some(pid) = pid {
+            process::unregister(pid);
+        }
+        process::halt_if_stopping().await;
+        match result {
+            Ok(()) => break,
+            // Only the first line, the hints are shown once the last attempt fails
+            Err(e) if attempt < attempts => display_error(e.to_string().lines().next().unwrap_or_default()),
+            Err(e) => return Err(e),
+        }
+    }
+    display_success(&format!("Script '{}' completed successfully", script_name));
+    if !script.inputs().is_empty() || !script.outputs().is_empty() {
+        // Without the tasks of the packages it depends on, a hash could miss their changes
+        display_tip(&format!(
+            "'{}' declares inputs/outputs, which are only cached by multi-target runs ('knot run {} --all' or --filter)",
+            script_name, script_name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate the script and build its shell command, returning it with the command line
+fn prepare_script(
+    script_name: &str,
+    script: &Script,
+    args: &[String],
+    source: &ScriptSource,
+) -> Result<(tokio::process::Command, String)> {
+    let (dir, context) = (source.dir.as_path(), source.context);
+    let script_command = tasks::with_args(script.command(), args, script.shell());
+    let working_dir = match script.cwd() {
+        Some(cwd) => dir.join(cwd),
+        None => dir.to_path_buf(),
+    };
+    let working_dir = working_dir.as_path();
+
     // Validate script name and command
     if script_name.is_empty() {
         anyhow::bail!("Script name cannot be empty when executing script\n💡 Provide a script name as argument: knot run <script-name>\n💡 Use 'knot run' without arguments for interactive selection");
@@ -245,49 +752,75 @@ async fn execute_script(
         );
     }
 
+    // Apps and packages outside a project still get their own .env files
+    let project = Project::find_and_load(dir).ok();
+    let env = env::script_env(project.as_ref(), source.node.as_ref(), dir, script)?;
+
     let spinner = create_spinner(&format!("Preparing to run {} script '{}'", context, script_name));
 
     println!("🚀 Running {} script '{}'...", context, script_name);
+    if let Some(description) = script.description() {
+        println!("ℹ️  {}", description);
+    }
     println!("📝 Command: {}", script_command);
     println!("📂 Working directory: {}", working_dir.display());
 
     spinner.finish_and_clear();
 
     // Use shell execution for complex commands (safer than manual parsing)
-    let shell = if cfg!(target_os = "windows") {
-        "cmd"
-    } else {
-        "sh"
-    };
-
-    let shell_flag = if cfg!(target_os = "windows") {
-        "/C"
-    } else {
-        "-c"
-    };
-
-    let mut cmd = tokio::process::Command::new(shell);
-    cmd.arg(shell_flag);
-    cmd.arg(script_command);
+    let mut cmd = tasks::shell_command(script.shell(), &script_command);
+    if script.env_passthrough().is_some() {
+        cmd.env_clear();
+    }
+    cmd.envs(env);
     cmd.current_dir(working_dir);
-    cmd.stdin(std::process::Stdio::inherit());
     cmd.stdout(std::process::Stdio::inherit());
     cmd.stderr(std::process::Stdio::inherit());
 
-    let status = cmd.status().await.with_context(|| {
-        format!(
-            "Failed to execute script '{}': {}",
-            script_name, script_command
-        )
-    })?;
+    Ok((cmd, script_command))
+}
 
-    if status.success() {
-        display_success(&format!("Script '{}' completed successfully", script_name));
-    } else {
-        let exit_code = status.code().unwrap_or(-1);
-        anyhow::bail!("Script '{}' failed with exit code {}\n💡 The script command '{}' returned an error\n💡 Check the command output above for error details\n💡 Verify the script is correct in your configuration file", script_name, exit_code, script_command);
+/// Wait for a started script, stopping it once it runs longer than its timeout
+async fn wait_for_script(
+    script_name: &str,
+    script: &Script,
+    child: &mut tokio::process::Child,
+) -> Result<std::process::ExitStatus> {
+    let Some(limit) = script.timeout() else {
+        return Ok(child.wait().await?);
+    };
+
+    match tokio::time::timeout(limit, child.wait()).await {
+        Ok(status) => Ok(status?),
+        Err(_) => {
+            match child.id() {
+                Some(pid) => stop_script_tree(pid, child).await,
+                None => {
+                    let _ = child.kill().await;
+                }
+            }
+            Err(tasks::ExitError {
+                code: TaskStatus::TimedOut.exit_code(),
+                message: format!(
+                    "Script '{}' timed out after {}\n💡 Raise the script's timeout or check whether the command hangs",
+                    script_name,
+                    summary::format_duration(limit)
+                ),
+            }
+            .into())
+        }
     }
+}
 
+fn check_status(script_name: &str, script_command: &str, status: std::process::ExitStatus) -> Result<()> {
+    if !status.success() {
+        let exit_code = status.code().unwrap_or(-1);
+        return Err(tasks::ExitError {
+            code: TaskStatus::Failed(exit_code).exit_code(),
+            message: format!("Script '{}' failed with exit code {}\n💡 The script command '{}' returned an error\n💡 Check the command output above for error details\n💡 Verify the script is correct in your configuration file", script_name, exit_code, script_command),
+        }
+        .into());
+    }
     Ok(())
 }
 
@@ -302,9 +835,7 @@ async fn show_available_scripts(current_dir: &Path, project: &Project) -> Result
             if let Some(scripts) = &app_config.scripts {
                 if !scripts.is_empty() {
                     println!("  📱 App scripts ({})", app_config.name);
-                    for (name, command) in scripts {
-                        println!("    • {} - {}", name, command);
-                    }
+                    print_scripts(scripts);
                     found_any = true;
                 }
             }
@@ -317,9 +848,7 @@ async fn show_available_scripts(current_dir: &Path, project: &Project) -> Result
             if let Some(scripts) = &package_config.scripts {
                 if !scripts.is_empty() {
                     println!("  📦 Package scripts ({})", package_config.name);
-                    for (name, command) in scripts {
-                        println!("    • {} - {}", name, command);
-                    }
+                    print_scripts(scripts);
                     found_any = true;
                 }
             }
@@ -330,9 +859,7 @@ async fn show_available_scripts(current_dir: &Path, project: &Project) -> Result
     if let Some(scripts) = &project.config.scripts {
         if !scripts.is_empty() {
             println!("  🏗️  Project scripts ({})", project.config.name);
-            for (name, command) in scripts {
-                println!("    • {} - {}", name, command);
-            }
+            print_scripts(scripts);
             found_any = true;
         }
     }
@@ -342,4 +869,13 @@ async fn show_available_scripts(current_dir: &Path, project: &Project) -> Result
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn print_scripts(scripts: &HashMap<String, Script>) {
+    for (name, script) in scripts {
+        match script.description() {
+            Some(description) => println!("    • {} - {} {}", name, description, style(format!("({})", script)).dim()),
+            None => println!("    • {} - {}", name, script),
+        }
+    }
+}
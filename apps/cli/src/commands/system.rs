@@ -1,6 +1,8 @@
 use anyhow::{Context, Result};
 use std::env;
 use crate::project::Project;
+use crate::toolchain;
+use console::style;
 use serde::{Deserialize, Serialize};
 
 // API response structures
@@ -97,6 +99,58 @@ pub fn show_status() -> Result<()> {
     Ok(())
 }
 
+/// Check the installed tools against the `tools` required by knot.yml and each app.yml
+pub fn doctor() -> Result<()> {
+    let current_dir = env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+
+    let mut sections = vec![("knot.yml".to_string(), toolchain::requirements(&project, None)?)];
+    let mut apps: Vec<&String> = project.apps.keys().collect();
+    apps.sort();
+    for app in apps {
+        // Only what the app declares itself, the rest is listed under knot.yml
+        let own: Vec<_> = toolchain::requirements(&project, Some(app))?
+            .into_iter()
+            .filter(|requirement| !requirement.is_project_wide())
+            .collect();
+        if !own.is_empty() {
+            sections.push((format!("app '{}'", app), own));
+        }
+    }
+
+    if sections.iter().all(|(_, requirements)| requirements.is_empty()) {
+        println!("🩺 No toolchain requirements declared");
+        println!("💡 Add them to knot.yml or an app.yml, e.g.\n   tools:\n     node: \">=20\"\n     bun: \"^1.1\"");
+        return Ok(());
+    }
+
+    println!("🩺 Checking toolchains of project '{}'...", project.config.name);
+    let mut failed = 0;
+    for (name, requirements) in sections.iter().filter(|(_, requirements)| !requirements.is_empty()) {
+        println!("\n{}", style(name).bold());
+        for check in toolchain::check(requirements) {
+            let icon = if check.is_ok() { "✅" } else { "❌" };
+            failed += usize::from(!check.is_ok());
+            println!(
+                "  {} {} {}: {}",
+                icon,
+                style(&check.requirement.tool).cyan(),
+                check.requirement.version,
+                check.outcome()
+            );
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} toolchain requirement(s) not met\n💡 Install or switch to the required versions, e.g. with nvm, fnm, volta or rustup",
+            failed
+        );
+    }
+    println!("\n✅ All toolchain requirements are met");
+    Ok(())
+}
+
 pub async fn auth_status() -> Result<()> {
     match get_auth_token()? {
         Some(token) => {
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use console::style;
+
+use crate::commands::common::{display_info, display_success};
+use crate::project::Project;
+use crate::vendor::{VendorStore, VENDOR_DIR};
+
+pub async fn vendor_packages() -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::find_and_load(&current_dir)?;
+
+    // Collect every online package referenced by an app or a local package
+    let app_names = project.get_app_names();
+    let mut remote_specs: Vec<String> = app_names
+        .iter()
+        .flat_map(|app_name| project.get_app_dependencies(app_name))
+        .chain(project.packages.values().flat_map(|package| package.dependencies.iter().flatten().cloned()))
+        .filter(|dep| dep.starts_with('@'))
+        .collect();
+    remote_specs.sort();
+    remote_specs.dedup();
+
+    if remote_specs.is_empty() {
+        display_info("No Knot Space packages are used by any app or package, nothing to vendor");
+        return Ok(());
+    }
+
+    println!(
+        "📦 Vendoring {} remote package(s) into {}...",
+        remote_specs.len(),
+        style(VENDOR_DIR).cyan()
+    );
+
+    let store = VendorStore::new(&project.root);
+    let manifest = store.vendor_all(&remote_specs).await?;
+
+    display_success(&format!(
+        "Vendored {} package version(s) into {}",
+        manifest.packages.len(),
+        store.root().display()
+    ));
+    display_info("Commit the vendor directory and use 'knot link --offline' in air-gapped builds");
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[allow(dead_code)]
 fn validate_safe_name_common(name: &str, context: &str) -> anyhow::Result<()> {
@@ -52,6 +52,192 @@ impl ConfigVariable {
     }
 }
 
+/// A script: a shell command, or a definition with metadata, environment and dependencies
+/// Example:
+/// scripts:
+///   lint: "eslint src"
+///   build:
+///     run: "tsc -p ."
+///     description: "Compile TypeScript"
+///     cwd: "src"
+///     env:
+///       NODE_ENV: production
+///     depends_on: ["lint", "^build"]
+///     shell: bash
+///     timeout: 300
+///     retries: 2
+///     inputs: ["src/**/*.ts", "tsconfig.json"]
+///     outputs: ["dist"]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum Script {
+    /// Plain shell command
+    Command(String),
+    /// Command with its metadata
+    Detailed {
+        run: String,
+        /// Shown by `knot run` when picking or listing scripts
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        /// Directory to run in, relative to the config file's directory
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+        /// Extra environment variables
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        env: HashMap<String, String>,
+        /// Run with a cleared environment, inheriting only these variables (or `NPM_*` style
+        /// globs) from knot's, on top of the variables knot adds
+        #[serde(skip_serializing_if = "Option::is_none")]
+        env_passthrough: Option<Vec<String>>,
+        /// Scripts to run first: `name` at the same config level, `^name` in every local
+        /// package this app or package depends on
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        depends_on: Vec<String>,
+        /// Shell used to run the command instead of `sh` (`cmd` on Windows)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        shell: Option<String>,
+        /// Stop the command after this many seconds
+        #[serde(skip_serializing_if = "Option::is_none")]
+        timeout: Option<u64>,
+        /// Run the command again up to this many times after it fails or times out
+        #[serde(skip_serializing_if = "Option::is_none")]
+        retries: Option<u32>,
+        /// Globs of input files, relative to the script's directory
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        inputs: Vec<String>,
+        /// Directories (or files) the command writes, relative to the script's directory
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        outputs: Vec<String>,
+    },
+}
+
+impl Script {
+    /// The shell command to run
+    pub fn command(&self) -> &str {
+        match self {
+            Script::Command(command) => command,
+            Script::Detailed { run, .. } => run,
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            Script::Command(_) => None,
+            Script::Detailed { description, .. } => description.as_deref(),
+        }
+    }
+
+    pub fn cwd(&self) -> Option<&str> {
+        match self {
+            Script::Command(_) => None,
+            Script::Detailed { cwd, .. } => cwd.as_deref(),
+        }
+    }
+
+    pub fn env(&self) -> Option<&HashMap<String, String>> {
+        match self {
+            Script::Command(_) => None,
+            Script::Detailed { env, .. } => Some(env),
+        }
+    }
+
+    /// Allowlist of inherited variables when the script runs hermetically
+    pub fn env_passthrough(&self) -> Option<&[String]> {
+        match self {
+            Script::Command(_) => None,
+            Script::Detailed { env_passthrough, .. } => env_passthrough.as_deref(),
+        }
+    }
+
+    pub fn depends_on(&self) -> &[String] {
+        match self {
+            Script::Command(_) => &[],
+            Script::Detailed { depends_on, .. } => depends_on,
+        }
+    }
+
+    pub fn shell(&self) -> Option<&str> {
+        match self {
+            Script::Command(_) => None,
+            Script::Detailed { shell, .. } => shell.as_deref(),
+        }
+    }
+
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        match self {
+            Script::Command(_) => None,
+            Script::Detailed { timeout, .. } => timeout.map(std::time::Duration::from_secs),
+        }
+    }
+
+    /// Extra attempts after a failure, 0 unless configured
+    pub fn retries(&self) -> u32 {
+        match self {
+            Script::Command(_) => 0,
+            Script::Detailed { retries, .. } => retries.unwrap_or(0),
+        }
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        match self {
+            Script::Command(_) => &[],
+            Script::Detailed { inputs, .. } => inputs,
+        }
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        match self {
+            Script::Command(_) => &[],
+            Script::Detailed { outputs, .. } => outputs,
+        }
+    }
+
+    pub fn validate(&self, name: &str) -> anyhow::Result<()> {
+        if self.command().trim().is_empty() {
+            anyhow::bail!("Script command cannot be empty for script '{}'", name);
+        }
+
+        let cwd = self.cwd().map(|cwd| cwd.to_string());
+        for path in self.inputs().iter().chain(self.outputs()).chain(&cwd) {
+            if path.trim().is_empty()
+                || std::path::Path::new(path).is_absolute()
+                || path.split(['/', '\\']).any(|part| part == "..")
+            {
+                anyhow::bail!(
+                    "Invalid path '{}' in cwd/inputs/outputs of script '{}'\n💡 Use paths relative to the script's directory, e.g. outputs: [\"dist\"]",
+                    path, name
+                );
+            }
+        }
+
+        for dependency in self.depends_on() {
+            let dependency_name = dependency.strip_prefix('^').unwrap_or(dependency);
+            if dependency_name.trim().is_empty() || dependency_name.contains(char::is_whitespace) {
+                anyhow::bail!(
+                    "Invalid entry '{}' in depends_on of script '{}'\n💡 Use a script name like \"lint\", or \"^build\" for the build script of dependencies",
+                    dependency, name
+                );
+            }
+            if dependency == name {
+                anyhow::bail!("Script '{}' cannot depend on itself", name);
+            }
+        }
+
+        if let Script::Detailed { timeout: Some(0), .. } = self {
+            anyhow::bail!("Timeout of script '{}' must be at least 1 second", name);
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Script {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.command())
+    }
+}
+
 #[allow(dead_code)]
 pub fn parse_yaml_error_to_user_friendly(error: &serde_yaml::Error) -> String {
     parse_yaml_error_with_context(error, ConfigType::Unknown)
@@ -333,7 +519,7 @@ pub struct KnotConfig {
     /// App dependencies configuration
     pub apps: Option<HashMap<String, AppDependencies>>,
     /// Project-level scripts
-    pub scripts: Option<HashMap<String, String>>,
+    pub scripts: Option<HashMap<String, Script>>,
     /// Project-level variables available to all apps and packages
     /// These variables can be referenced using {{variable_name}} syntax
     /// Example:
@@ -341,6 +527,105 @@ pub struct KnotConfig {
     ///   project_version: "1.0.0"
     ///   api_url: "https://api.example.com"
     pub variables: Option<HashMap<String, ConfigVariable>>,
+    /// Dependency resolution cache settings (override the user config)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheConfig>,
+    /// Shared task cache server (overrides the user config)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_cache: Option<RemoteCacheConfig>,
+    /// Tool versions every script needs, checked by `knot run` and `knot doctor`
+    /// Example:
+    /// tools:
+    ///   node: ">=20"
+    ///   cargo: "*"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<BTreeMap<String, String>>,
+}
+
+/// Dependency resolution cache settings, from knot.yml or ~/.knot/config.yml
+/// Example:
+/// cache:
+///   ttl: 7200
+///   max_entries: 500
+///   max_memory_mb: 50
+///   fingerprint_sources: true
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// How long cached resolutions stay valid, in seconds
+    pub ttl: Option<u64>,
+    /// Maximum number of resolutions kept in memory
+    pub max_entries: Option<usize>,
+    /// Maximum memory used by cached resolutions, in megabytes
+    pub max_memory_mb: Option<usize>,
+    /// Also hash local package sources (respecting .knotignore) when invalidating resolutions
+    pub fingerprint_sources: Option<bool>,
+}
+
+impl CacheConfig {
+    /// Combine two configs, preferring values set in `self`
+    pub fn or(&self, fallback: &CacheConfig) -> CacheConfig {
+        CacheConfig {
+            ttl: self.ttl.or(fallback.ttl),
+            max_entries: self.max_entries.or(fallback.max_entries),
+            max_memory_mb: self.max_memory_mb.or(fallback.max_memory_mb),
+            fingerprint_sources: self.fingerprint_sources.or(fallback.fingerprint_sources),
+        }
+    }
+}
+
+/// Shared task cache server, from knot.yml or ~/.knot/config.yml. The server stores
+/// task output tarballs with `GET`/`PUT /cache/<hash>`.
+/// Example:
+/// remote_cache:
+///   url: "https://cache.example.com"
+///   token: "secret"      # or set KNOT_REMOTE_CACHE_TOKEN
+///   read_only: true      # download only, leave uploads to CI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteCacheConfig {
+    /// Base URL of the cache server
+    pub url: String,
+    /// Bearer token sent with every request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Never upload task outputs
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// Per-user configuration stored in ~/.knot/config.yml
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UserConfig {
+    /// Default dependency resolution cache settings
+    pub cache: Option<CacheConfig>,
+    /// Default shared task cache server
+    pub remote_cache: Option<RemoteCacheConfig>,
+}
+
+impl UserConfig {
+    /// Location of the user config file
+    pub fn path() -> Option<std::path::PathBuf> {
+        dirs::home_dir().map(|home| home.join(".knot").join("config.yml"))
+    }
+
+    /// Load the user config, returning defaults when the file does not exist
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read user config file: {}", path.display()))?;
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+
+        serde_yaml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("{}", parse_yaml_error_with_context(&e, ConfigType::Unknown)))
+            .with_context(|| format!("Failed to parse user config file: {}", path.display()))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -412,7 +697,7 @@ pub struct PackageConfig {
     /// Package tags
     pub tags: Option<Vec<String>>,
     /// Package-level scripts
-    pub scripts: Option<HashMap<String, String>>,
+    pub scripts: Option<HashMap<String, Script>>,
     /// Package dependencies
     pub dependencies: Option<Vec<String>>,
     /// Development dependencies
@@ -434,6 +719,78 @@ pub struct PackageConfig {
     pub variables: Option<HashMap<String, ConfigVariable>>,
 }
 
+/// How `knot dev` runs an app's `dev` script
+/// Example:
+/// dev:
+///   depends_on: [api]      # apps started, and ready, first
+///   ready:
+///     port: 3000           # or log: "Listening on"
+///     timeout: 120
+///   restart: true          # restart the script when it crashes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DevConfig {
+    /// Apps whose dev scripts must be ready before this one starts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// When the app counts as ready; right after starting when not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ready: Option<ReadyCheck>,
+    /// Restart the dev script after it exits with an error, up to 5 times in a row
+    #[serde(default)]
+    pub restart: bool,
+}
+
+/// Readiness check of a dev script: a TCP port accepting connections, or a log line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadyCheck {
+    /// Port on localhost that accepts connections once the app is ready
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Regex matched against each output line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+    /// Seconds to wait for readiness (default 120)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+impl ReadyCheck {
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout.unwrap_or(120))
+    }
+}
+
+impl DevConfig {
+    pub fn validate(&self, app_name: &str) -> anyhow::Result<()> {
+        for dependency in &self.depends_on {
+            if dependency == app_name {
+                anyhow::bail!("App '{}' cannot list itself in dev.depends_on", app_name);
+            }
+            validate_safe_name_common(dependency, "dev.depends_on entry")?;
+        }
+
+        if let Some(ready) = &self.ready {
+            match (&ready.port, &ready.log) {
+                (Some(_), Some(_)) | (None, None) => anyhow::bail!(
+                    "dev.ready of app '{}' needs exactly one of port or log\n💡 Example: ready: {{ port: 3000 }} or ready: {{ log: \"Listening on\" }}",
+                    app_name
+                ),
+                (None, Some(pattern)) => {
+                    regex::Regex::new(pattern).with_context(|| {
+                        format!("Invalid dev.ready.log regex '{}' in app '{}'", pattern, app_name)
+                    })?;
+                }
+                (Some(_), None) => {}
+            }
+            if ready.timeout == Some(0) {
+                anyhow::bail!("dev.ready.timeout of app '{}' must be at least 1 second", app_name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// App-level configuration structure
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -446,8 +803,14 @@ pub struct AppConfig {
     pub ts_alias: Option<TsAlias>,
     /// Package dependencies for this app
     pub packages: Option<Vec<String>>,
+    /// Build command run by `knot build` after the app's local packages are built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
     /// App-level scripts
-    pub scripts: Option<HashMap<String, String>>,
+    pub scripts: Option<HashMap<String, Script>>,
+    /// How `knot dev` runs the `dev` script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dev: Option<DevConfig>,
     /// App-level variables that can override project variables
     /// These variables can be referenced using {{variable_name}} syntax
     /// Example:
@@ -455,6 +818,12 @@ pub struct AppConfig {
     ///   app_port: "3000"
     ///   app_title: "My Application"
     pub variables: Option<HashMap<String, ConfigVariable>>,
+    /// Tool versions the app's scripts need, replacing the project's for the same tool
+    /// Example:
+    /// tools:
+    ///   bun: "^1.1"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<BTreeMap<String, String>>,
 }
 
 impl KnotConfig {
@@ -488,16 +857,14 @@ impl KnotConfig {
 
         // Validate script names and commands
         if let Some(scripts) = &self.scripts {
-            for (name, command) in scripts {
+            for (name, script) in scripts {
                 if name.trim().is_empty() {
                     anyhow::bail!("Script name cannot be empty");
                 }
                 if name.contains(char::is_whitespace) {
                     anyhow::bail!("Script name cannot contain whitespace: '{}'", name);
                 }
-                if command.trim().is_empty() {
-                    anyhow::bail!("Script command cannot be empty for script '{}'", name);
-                }
+                script.validate(name)?;
                 self.validate_safe_name(name, "Script name")?;
             }
         }
@@ -601,16 +968,14 @@ impl PackageConfig {
 
         // Validate scripts
         if let Some(scripts) = &self.scripts {
-            for (name, command) in scripts {
+            for (name, script) in scripts {
                 if name.trim().is_empty() {
                     anyhow::bail!("Script name cannot be empty");
                 }
                 if name.contains(char::is_whitespace) {
                     anyhow::bail!("Script name cannot contain whitespace: '{}'", name);
                 }
-                if command.trim().is_empty() {
-                    anyhow::bail!("Script command cannot be empty for script '{}'", name);
-                }
+                script.validate(name)?;
                 self.validate_safe_name(name, "Script name")?;
             }
         }
@@ -655,16 +1020,14 @@ impl AppConfig {
 
         // Validate scripts
         if let Some(scripts) = &self.scripts {
-            for (name, command) in scripts {
+            for (name, script) in scripts {
                 if name.trim().is_empty() {
                     anyhow::bail!("Script name cannot be empty");
                 }
                 if name.contains(char::is_whitespace) {
                     anyhow::bail!("Script name cannot contain whitespace: '{}'", name);
                 }
-                if command.trim().is_empty() {
-                    anyhow::bail!("Script command cannot be empty for script '{}'", name);
-                }
+                script.validate(name)?;
                 self.validate_safe_name(name, "Script name")?;
             }
         }
@@ -680,9 +1043,26 @@ impl AppConfig {
             }
         }
 
+        if let Some(dev) = &self.dev {
+            dev.validate(&self.name)?;
+        }
+
+        if let Some(build) = &self.build {
+            if build.trim().is_empty() {
+                anyhow::bail!("Build command cannot be empty in app '{}'\n💡 Remove the build field or set a command\n💡 Example: build: \"npm run build\"", self.name);
+            }
+        }
+
         Ok(())
     }
 
+    /// Command used to build the app: `build`, falling back to the `build` script
+    pub fn build_command(&self) -> Option<&str> {
+        self.build
+            .as_deref()
+            .or_else(|| self.scripts.as_ref()?.get("build").map(|s| s.command()))
+    }
+
     #[allow(dead_code)]
     fn validate_safe_name(&self, name: &str, context: &str) -> anyhow::Result<()> {
         validate_safe_name_common(name, context)
@@ -5,18 +5,20 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tokio::sync::RwLock;
 
+use crate::config::CacheConfig;
 use crate::dependency::types::{ResolutionRequest, ResolutionResult as TypesResolutionResult, PackageId};
 use crate::dependency::error::{ResolutionError, ResolutionResult};
 
 #[derive(Debug)]
 pub struct ResolutionCache {
     memory_cache: RwLock<HashMap<String, CacheEntry>>,
-    #[allow(dead_code)]
     package_cache: RwLock<HashMap<PackageId, PackageCacheEntry>>,
     disk_cache_path: PathBuf,
+    package_cache_path: PathBuf,
     cache_ttl: Duration,
     max_memory_entries: usize,
     max_memory_size: usize, // in bytes
+    fingerprint_sources: bool,
     cache_statistics: RwLock<CacheStatistics>,
 }
 
@@ -30,12 +32,16 @@ struct CacheEntry {
     size_bytes: usize,
 }
 
+/// Versions of a remote package as listed by its registry. Local packages are read from disk on
+/// every resolution; their fingerprints in `hash_request` invalidate cached resolutions instead.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct PackageCacheEntry {
     versions: Vec<crate::dependency::types::PackageVersion>,
     timestamp: SystemTime,
     last_access: SystemTime,
-    source_fingerprint: String, // Hash of source directory for local packages
+    /// URL of the registry the versions were listed from
+    #[serde(default)]
+    registry_url: String,
 }
 
 #[derive(Debug, Clone)]
@@ -53,9 +59,11 @@ impl ResolutionCache {
             memory_cache: RwLock::new(HashMap::with_capacity(128)),
             package_cache: RwLock::new(HashMap::with_capacity(64)),
             disk_cache_path: cache_dir.join("resolutions"),
+            package_cache_path: cache_dir.join("packages"),
             cache_ttl: Duration::from_secs(3600),
             max_memory_entries: 1000,
             max_memory_size: 100 * 1024 * 1024,
+            fingerprint_sources: false,
             cache_statistics: RwLock::new(CacheStatistics {
                 hits: 0,
                 misses: 0,
@@ -66,19 +74,36 @@ impl ResolutionCache {
         }
     }
 
-    #[allow(dead_code)]
     pub fn with_ttl(mut self, ttl: Duration) -> Self {
         self.cache_ttl = ttl;
         self
     }
 
-    #[allow(dead_code)]
     pub fn with_memory_limits(mut self, max_entries: usize, max_size: usize) -> Self {
         self.max_memory_entries = max_entries;
         self.max_memory_size = max_size;
         self
     }
 
+    /// Apply TTL and memory limits from knot.yml / user config, keeping defaults for unset values
+    pub fn with_config(self, config: &CacheConfig) -> Self {
+        let ttl = config.ttl.map(Duration::from_secs).unwrap_or(self.cache_ttl);
+        let max_entries = config.max_entries.unwrap_or(self.max_memory_entries);
+        let max_size = config
+            .max_memory_mb
+            .map(|mb| mb * 1024 * 1024)
+            .unwrap_or(self.max_memory_size);
+
+        let mut cache = self.with_ttl(ttl).with_memory_limits(max_entries, max_size);
+        cache.fingerprint_sources = config.fingerprint_sources.unwrap_or(false);
+        cache
+    }
+
+    /// Whether local package sources, not only their configs, invalidate cached resolutions
+    pub fn fingerprint_sources(&self) -> bool {
+        self.fingerprint_sources
+    }
+
     pub async fn get_cached_resolution(
         &self, 
         request: &ResolutionRequest
@@ -166,7 +191,6 @@ impl ResolutionCache {
         Ok(())
     }
     
-    #[allow(dead_code)]
     pub async fn invalidate_package_cache(&self, package_name: &str) -> ResolutionResult<()> {
         // Remove from memory cache
         {
@@ -196,11 +220,11 @@ impl ResolutionCache {
         
         // Remove from disk cache
         self.cleanup_disk_cache_for_package(package_name).await?;
+        self.cleanup_package_entries_for_package(package_name).await;
         
         Ok(())
     }
     
-    #[allow(dead_code)]
     pub async fn clear_cache(&self) -> ResolutionResult<()> {
         // Clear memory caches
         {
@@ -232,11 +256,17 @@ impl ResolutionCache {
                     e.to_string()
                 ))?;
         }
+        if self.package_cache_path.exists() {
+            tokio::fs::remove_dir_all(&self.package_cache_path).await
+                .map_err(|e| ResolutionError::cache_error(
+                    "clearing package cache",
+                    e.to_string()
+                ))?;
+        }
         
         Ok(())
     }
     
-    #[allow(dead_code)]
     pub async fn cleanup_expired(&self) -> ResolutionResult<()> {
         // Clean memory cache
         {
@@ -270,7 +300,6 @@ impl ResolutionCache {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub async fn cache_stats(&self) -> CacheStats {
         let memory_cache = self.memory_cache.read().await;
         let package_cache = self.package_cache.read().await;
@@ -285,9 +314,13 @@ impl ResolutionCache {
         } else {
             0.0
         };
+
+        let (disk_entries, disk_size_bytes) = self.disk_usage();
         
         CacheStats {
             memory_entries,
+            disk_entries,
+            disk_size_bytes,
             package_entries,
             memory_size_bytes: memory_size,
             disk_path: self.disk_cache_path.clone(),
@@ -300,6 +333,18 @@ impl ResolutionCache {
         }
     }
     
+    fn disk_usage(&self) -> (usize, u64) {
+        let Ok(entries) = std::fs::read_dir(&self.disk_cache_path) else {
+            return (0, 0);
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("json"))
+            .filter_map(|entry| entry.metadata().ok())
+            .fold((0, 0), |(count, size), metadata| (count + 1, size + metadata.len()))
+    }
+    
     fn hash_request(&self, request: &ResolutionRequest) -> String {
         let mut hasher = Sha256::new();
         
@@ -356,6 +401,12 @@ impl ResolutionCache {
         for package_id in &request.excludes {
             hasher.update(package_id.name.as_bytes());
         }
+
+        // Hash local package fingerprints (BTreeMap keeps the order stable)
+        for (package_name, fingerprint) in &request.fingerprints {
+            hasher.update(package_name.as_bytes());
+            hasher.update(fingerprint.as_bytes());
+        }
         
         format!("{:x}", hasher.finalize())
     }
@@ -500,25 +551,36 @@ impl ResolutionCache {
     }
 
     // Package-specific caching methods
-    #[allow(dead_code)]
-    pub async fn cache_package_versions(&self, package_id: &PackageId, versions: Vec<crate::dependency::types::PackageVersion>, fingerprint: String) {
+    /// Remember the versions published for a package, in memory and on disk so they
+    /// remain available when the registry cannot be reached
+    pub async fn cache_package_versions(&self, package_id: &PackageId, versions: Vec<crate::dependency::types::PackageVersion>, registry_url: &str) -> ResolutionResult<()> {
         let entry = PackageCacheEntry {
             versions,
             timestamp: SystemTime::now(),
             last_access: SystemTime::now(),
-            source_fingerprint: fingerprint,
+            registry_url: registry_url.to_string(),
         };
-        
+
+        self.save_package_entry(package_id, &entry).await?;
+
         let mut package_cache = self.package_cache.write().await;
         package_cache.insert(package_id.clone(), entry);
+        Ok(())
     }
 
-    #[allow(dead_code)]
-    pub async fn get_cached_package_versions(&self, package_id: &PackageId, current_fingerprint: &str) -> Option<Vec<crate::dependency::types::PackageVersion>> {
+    /// Versions of a package last listed from `registry_url`. Entries older than the cache TTL
+    /// only count with `allow_expired`, e.g. when working offline.
+    pub async fn get_cached_package_versions(&self, package_id: &PackageId, registry_url: &str, allow_expired: bool) -> Option<Vec<crate::dependency::types::PackageVersion>> {
         let mut package_cache = self.package_cache.write().await;
+
+        if !package_cache.contains_key(package_id) {
+            let entry = self.load_package_entry(package_id).await?;
+            package_cache.insert(package_id.clone(), entry);
+        }
         
         if let Some(entry) = package_cache.get_mut(package_id) {
-            if !self.is_cache_expired(&entry.timestamp) && entry.source_fingerprint == current_fingerprint {
+            let fresh = allow_expired || !self.is_cache_expired(&entry.timestamp);
+            if fresh && entry.registry_url == registry_url {
                 entry.last_access = SystemTime::now();
                 return Some(entry.versions.clone());
             } else {
@@ -529,8 +591,59 @@ impl ResolutionCache {
         
         None
     }
+
+    fn package_entry_path(&self, package_id: &PackageId) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(package_id.name.as_bytes());
+        if let crate::dependency::types::PackageSource::Remote { registry } = &package_id.source {
+            hasher.update(registry.as_bytes());
+        }
+        self.package_cache_path.join(format!("{:x}.json", hasher.finalize()))
+    }
+
+    async fn load_package_entry(&self, package_id: &PackageId) -> Option<PackageCacheEntry> {
+        let content = tokio::fs::read_to_string(self.package_entry_path(package_id)).await.ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    async fn save_package_entry(&self, package_id: &PackageId, entry: &PackageCacheEntry) -> ResolutionResult<()> {
+        tokio::fs::create_dir_all(&self.package_cache_path).await
+            .map_err(|e| ResolutionError::cache_error(
+                "creating package cache directory",
+                e.to_string()
+            ))?;
+
+        let content = serde_json::to_string(entry)
+            .map_err(|e| ResolutionError::cache_error(
+                "serializing package cache entry",
+                e.to_string()
+            ))?;
+
+        tokio::fs::write(self.package_entry_path(package_id), content).await
+            .map_err(|e| ResolutionError::cache_error(
+                "writing package cache file",
+                e.to_string()
+            ))
+    }
     
-    #[allow(dead_code)]
+    async fn cleanup_package_entries_for_package(&self, package_name: &str) {
+        let Ok(mut entries) = tokio::fs::read_dir(&self.package_cache_path).await else {
+            return;
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let Ok(content) = tokio::fs::read_to_string(&path).await else {
+                continue;
+            };
+            if let Ok(package_entry) = serde_json::from_str::<PackageCacheEntry>(&content) {
+                if package_entry.versions.iter().any(|v| v.id.name.contains(package_name)) {
+                    let _ = tokio::fs::remove_file(&path).await;
+                }
+            }
+        }
+    }
+
     async fn cleanup_disk_cache_for_package(&self, package_name: &str) -> ResolutionResult<()> {
         if !self.disk_cache_path.exists() {
             return Ok(());
@@ -565,7 +678,6 @@ impl ResolutionCache {
         Ok(())
     }
     
-    #[allow(dead_code)]
     async fn cleanup_expired_disk_cache(&self) -> ResolutionResult<()> {
         if !self.disk_cache_path.exists() {
             return Ok(());
@@ -611,6 +723,8 @@ impl ResolutionCache {
 #[derive(Debug)]
 pub struct CacheStats {
     pub memory_entries: usize,
+    pub disk_entries: usize,
+    pub disk_size_bytes: u64,
     pub package_entries: usize,
     pub memory_size_bytes: usize,
     pub disk_path: PathBuf,
@@ -625,12 +739,16 @@ pub struct CacheStats {
 #[allow(dead_code)]
 impl CacheStats {
     pub fn format_size(&self) -> String {
-        if self.memory_size_bytes < 1024 {
-            format!("{} B", self.memory_size_bytes)
-        } else if self.memory_size_bytes < 1024 * 1024 {
-            format!("{:.1} KB", self.memory_size_bytes as f64 / 1024.0)
+        Self::format_bytes(self.memory_size_bytes as u64)
+    }
+
+    fn format_bytes(bytes: u64) -> String {
+        if bytes < 1024 {
+            format!("{} B", bytes)
+        } else if bytes < 1024 * 1024 {
+            format!("{:.1} KB", bytes as f64 / 1024.0)
         } else {
-            format!("{:.1} MB", self.memory_size_bytes as f64 / (1024.0 * 1024.0))
+            format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
         }
     }
     
@@ -664,6 +782,9 @@ impl CacheStats {
         use console::style;
         
         println!("\n📊 Cache Performance Report:");
+        println!("  Disk Path: {}", style(self.disk_path.display()).dim());
+        println!("  Disk Entries: {}", style(self.disk_entries).cyan());
+        println!("  Disk Usage: {}", style(Self::format_bytes(self.disk_size_bytes)).yellow());
         println!("  Memory Entries: {}", style(self.memory_entries).cyan());
         println!("  Package Entries: {}", style(self.package_entries).cyan());
         println!("  Memory Usage: {}", style(self.format_size()).yellow());
@@ -676,7 +797,9 @@ impl CacheStats {
         println!("  Efficiency Score: {:.2}/1.0", style(format!("{:.2}", self.efficiency_score())).cyan());
         
         // Performance indicators
-        if self.hit_rate >= 80.0 {
+        if self.total_requests == 0 {
+            println!("  ℹ️  No lookups recorded in this session");
+        } else if self.hit_rate >= 80.0 {
             println!("  ✅ Excellent cache performance");
         } else if self.hit_rate >= 60.0 {
             println!("  ⚠️  Good cache performance, consider increasing TTL");
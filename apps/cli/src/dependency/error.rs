@@ -26,6 +26,9 @@ pub enum ResolutionError {
         error: String,
         retry_suggestion: String,
     },
+    OfflineUnavailable {
+        packages: Vec<PackageId>,
+    },
     InvalidVersion {
         package: PackageId,
         version: String,
@@ -88,6 +91,13 @@ impl fmt::Display for ResolutionError {
                 write!(f, "❌ Network error downloading '{}': {}\n\n💡 {}", 
                       package.name, error, retry_suggestion)
             }
+            ResolutionError::OfflineUnavailable { packages } => {
+                writeln!(f, "❌ Cannot resolve offline: {} package(s) are not in the local cache:", packages.len())?;
+                for package in packages {
+                    writeln!(f, "  • {}", package.name)?;
+                }
+                write!(f, "\n💡 Run the same command once with network access to populate the cache\n💡 Or vendor the packages with 'knot vendor'")
+            }
             ResolutionError::InvalidVersion { package, version, error } => {
                 write!(f, "❌ Invalid version '{}' for package '{}': {}", 
                       version, package.name, error)
@@ -172,10 +182,14 @@ impl ResolutionError {
         ResolutionError::NetworkError {
             package,
             error: error.into(),
-            retry_suggestion: "Check your network connection and try again. You can also try clearing the cache with 'knot cache clean'.".to_string(),
+            retry_suggestion: "Check your network connection and try again. You can also try clearing the cache with 'knot cache clear'.".to_string(),
         }
     }
 
+    pub fn offline_unavailable(packages: Vec<PackageId>) -> Self {
+        ResolutionError::OfflineUnavailable { packages }
+    }
+
     pub fn invalid_version(package: PackageId, version: impl Into<String>, error: impl Into<String>) -> Self {
         ResolutionError::InvalidVersion {
             package,
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use sha2::{Digest, Sha256};
+
+use crate::ignore::KnotIgnore;
+
+/// Config files whose contents determine how a local package resolves
+const CONFIG_FILES: [&str; 2] = ["package.yml", "package.yaml"];
+
+/// Fingerprint a local package from its config and, optionally, its sources.
+/// Source files matching the package's `.knotignore` (or the default patterns) are skipped.
+pub fn package_fingerprint(package_dir: &Path, include_sources: bool) -> String {
+    let mut hasher = Sha256::new();
+
+    for file_name in CONFIG_FILES {
+        if let Ok(content) = fs::read(package_dir.join(file_name)) {
+            hasher.update(file_name.as_bytes());
+            hasher.update(&content);
+        }
+    }
+
+    if include_sources {
+        let ignore = KnotIgnore::from_file(&package_dir.join(".knotignore"))
+            .unwrap_or_else(|_| KnotIgnore::default());
+        let mut files = Vec::new();
+        collect_source_files(package_dir, "", &ignore, &mut files);
+        files.sort();
+
+        for relative_path in files {
+            if let Ok(content) = fs::read(package_dir.join(&relative_path)) {
+                hasher.update(relative_path.as_bytes());
+                hasher.update(&content);
+            }
+        }
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// Fingerprints of every package directory under `packages_dir`, keyed by directory name
+pub fn packages_fingerprints(packages_dir: &Path, include_sources: bool) -> BTreeMap<String, String> {
+    let Ok(entries) = fs::read_dir(packages_dir) else {
+        return BTreeMap::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            (name, package_fingerprint(&entry.path(), include_sources))
+        })
+        .collect()
+}
+
+fn collect_source_files(base: &Path, relative: &str, ignore: &KnotIgnore, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(base.join(relative)) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let relative_path = if relative.is_empty() {
+            file_name.clone()
+        } else {
+            format!("{}/{}", relative, file_name)
+        };
+
+        if ignore.is_ignored(&relative_path) || CONFIG_FILES.contains(&relative_path.as_str()) {
+            continue;
+        }
+
+        let path = entry.path();
+        if path.is_dir() {
+            collect_source_files(base, &relative_path, ignore, files);
+        } else if path.is_file() {
+            files.push(relative_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_config_change_changes_fingerprint() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("package.yml"), "name: utils\nversion: 1.0.0\n").unwrap();
+
+        let before = package_fingerprint(dir, false);
+        assert_eq!(before, package_fingerprint(dir, false));
+
+        fs::write(dir.join("package.yml"), "name: utils\nversion: 1.0.0\ndependencies:\n  - types\n").unwrap();
+        assert_ne!(before, package_fingerprint(dir, false));
+    }
+
+    #[test]
+    fn test_sources_respect_knotignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("package.yml"), "name: utils\nversion: 1.0.0\n").unwrap();
+        fs::write(dir.join(".knotignore"), "dist/\n").unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("dist")).unwrap();
+        fs::write(dir.join("src/index.ts"), "export const a = 1;").unwrap();
+
+        let config_only = package_fingerprint(dir, false);
+        let with_sources = package_fingerprint(dir, true);
+
+        // Ignored build output does not affect the fingerprint
+        fs::write(dir.join("dist/index.js"), "exports.a = 1;").unwrap();
+        assert_eq!(with_sources, package_fingerprint(dir, true));
+
+        // Source edits only matter when sources are included
+        fs::write(dir.join("src/index.ts"), "export const a = 2;").unwrap();
+        assert_ne!(with_sources, package_fingerprint(dir, true));
+        assert_eq!(config_only, package_fingerprint(dir, false));
+    }
+}
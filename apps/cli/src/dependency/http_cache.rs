@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A registry response stored with the validators needed to revalidate it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpCacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+/// On-disk cache of registry responses keyed by URL, revalidated with
+/// `If-None-Match` / `If-Modified-Since` so unchanged responses cost a 304
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+}
+
+impl HttpCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_bytes());
+        self.dir.join(format!("{:x}.json", hasher.finalize()))
+    }
+
+    pub fn load(&self, url: &str) -> Option<HttpCacheEntry> {
+        let content = fs::read_to_string(self.entry_path(url)).ok()?;
+        serde_json::from_str::<HttpCacheEntry>(&content)
+            .ok()
+            .filter(|entry| entry.url == url)
+    }
+
+    /// Store a response; entries without validators are skipped since they can never be revalidated
+    pub fn store(&self, entry: &HttpCacheEntry) -> std::io::Result<()> {
+        if entry.etag.is_none() && entry.last_modified.is_none() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string(entry)?;
+        fs::write(self.entry_path(&entry.url), content)
+    }
+}
@@ -3,6 +3,8 @@ pub mod registry;
 pub mod types;
 pub mod cache;
 pub mod error;
+pub mod fingerprint;
+pub mod http_cache;
 
 pub use resolver::DependencyResolver;
 pub use types::{PackageId, PackageVersion, DependencySpec, ResolutionContext, ResolutionStrategy};
@@ -7,6 +7,7 @@ use serde::Deserialize;
 use crate::config::PackageConfig;
 use crate::dependency::types::{PackageId, PackageVersion, PackageSource, PackageMetadata, DependencySpec};
 use crate::dependency::error::{ResolutionError, ResolutionResult};
+use crate::dependency::http_cache::{HttpCache, HttpCacheEntry};
 
 #[derive(Deserialize)]
 struct RemoteDependency {
@@ -38,6 +39,10 @@ impl LocalPackageRegistry {
         }
     }
 
+    pub fn packages_path(&self) -> &Path {
+        &self.packages_path
+    }
+
     pub async fn discover_packages(&mut self) -> ResolutionResult<()> {
         self.cache.clear();
         
@@ -261,8 +266,8 @@ impl PackageRegistry for LocalPackageRegistry {
 pub struct RemotePackageRegistry {
     base_url: String,
     client: reqwest::Client,
-    cache: HashMap<PackageId, Vec<PackageVersion>>,
     auth_token: Option<String>,
+    http_cache: Option<HttpCache>,
 }
 
 impl RemotePackageRegistry {
@@ -270,11 +275,21 @@ impl RemotePackageRegistry {
         Self {
             base_url,
             client: reqwest::Client::new(),
-            cache: HashMap::new(),
             auth_token: None,
+            http_cache: None,
         }
     }
 
+    /// Persist responses with their ETag/Last-Modified and revalidate them on later requests
+    pub fn with_http_cache(mut self, dir: PathBuf) -> Self {
+        self.http_cache = Some(HttpCache::new(dir));
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     #[allow(dead_code)]
     pub fn with_auth(mut self, token: String) -> Self {
         self.auth_token = Some(token);
@@ -290,27 +305,77 @@ impl RemotePackageRegistry {
         
         request
     }
-}
 
-#[async_trait]
-impl PackageRegistry for RemotePackageRegistry {
-    async fn list_versions(&self, package_id: &PackageId) -> ResolutionResult<Vec<PackageVersion>> {
-        if let Some(cached) = self.cache.get(package_id) {
-            return Ok(cached.clone());
+    /// GET a registry URL, revalidating any cached copy. Returns `None` when the
+    /// registry answers with a non-success status.
+    async fn get_text(&self, package_id: &PackageId, url: &str) -> ResolutionResult<Option<String>> {
+        let cached = self.http_cache.as_ref().and_then(|cache| cache.load(url));
+
+        let mut request = self.build_request(url);
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
         }
 
-        let package_name = package_id.name.trim_start_matches('@');
-        let url = format!("{}/api/packages/{}/versions", self.base_url, package_name);
-        
-        let response = self.build_request(&url)
+        let response = request
             .send()
             .await
             .map_err(|e| ResolutionError::network_error(package_id.clone(), e.to_string()))?;
 
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                return Ok(Some(entry.body));
+            }
+        }
+
         if !response.status().is_success() {
-            return Ok(Vec::new()); // Package not found
+            return Ok(None);
         }
 
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+        let body = response.text().await
+            .map_err(|e| ResolutionError::network_error(package_id.clone(),
+                format!("Failed to read response: {}", e)))?;
+
+        if let Some(cache) = &self.http_cache {
+            let entry = HttpCacheEntry {
+                url: url.to_string(),
+                etag,
+                last_modified,
+                body,
+            };
+            // A failed cache write only costs a full download next time
+            let _ = cache.store(&entry);
+            return Ok(Some(entry.body));
+        }
+
+        Ok(Some(body))
+    }
+}
+
+#[async_trait]
+impl PackageRegistry for RemotePackageRegistry {
+    async fn list_versions(&self, package_id: &PackageId) -> ResolutionResult<Vec<PackageVersion>> {
+        let package_name = package_id.name.trim_start_matches('@');
+        let url = format!("{}/api/packages/{}/versions", self.base_url, package_name);
+        
+        let Some(body) = self.get_text(package_id, &url).await? else {
+            return Ok(Vec::new()); // Package not found
+        };
+
         #[derive(Deserialize)]
         struct VersionsResponse {
             data: Vec<RemotePackageVersion>,
@@ -325,7 +390,7 @@ impl PackageRegistry for RemotePackageRegistry {
         }
 
 
-        let versions_data: VersionsResponse = response.json().await
+        let versions_data: VersionsResponse = serde_json::from_str(&body)
             .map_err(|e| ResolutionError::network_error(package_id.clone(), 
                 format!("Failed to parse response: {}", e)))?;
 
@@ -360,20 +425,15 @@ impl PackageRegistry for RemotePackageRegistry {
         let package_name = package_id.name.trim_start_matches('@');
         let url = format!("{}/api/packages/{}/{}", self.base_url, package_name, version);
         
-        let response = self.build_request(&url)
-            .send()
-            .await
-            .map_err(|e| ResolutionError::network_error(package_id.clone(), e.to_string()))?;
-
-        if !response.status().is_success() {
+        let Some(body) = self.get_text(package_id, &url).await? else {
             return Err(ResolutionError::package_not_found(
                 package_id.clone(),
                 vec![format!("Remote registry: {}", self.base_url)],
                 vec![]
             ));
-        }
+        };
 
-        let metadata: PackageMetadata = response.json().await
+        let metadata: PackageMetadata = serde_json::from_str(&body)
             .map_err(|e| ResolutionError::network_error(package_id.clone(), 
                 format!("Failed to parse metadata: {}", e)))?;
 
@@ -515,4 +575,62 @@ fn extract_package(data: &[u8], destination: &Path) -> std::io::Result<()> {
     std::io::copy(&mut Cursor::new(data), &mut file)?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Serve `responses` in order, recording each raw request
+    fn serve(responses: Vec<String>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buffer = [0u8; 4096];
+                let read = stream.read(&mut buffer).unwrap();
+                seen.lock().unwrap().push(String::from_utf8_lossy(&buffer[..read]).to_lowercase());
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    #[tokio::test]
+    async fn test_versions_revalidated_with_etag() {
+        let body = r#"{"data":[{"version":"1.2.0"},{"version":"1.0.0"}]}"#;
+        let (base_url, requests) = serve(vec![
+            format!(
+                "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+        ]);
+
+        let cache_dir = TempDir::new().unwrap();
+        let registry = RemotePackageRegistry::new(base_url)
+            .with_http_cache(cache_dir.path().to_path_buf());
+        let package_id = PackageId::remote("@team/utils", "knot-space");
+
+        let first = registry.list_versions(&package_id).await.unwrap();
+        let second = registry.list_versions(&package_id).await.unwrap();
+
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[0].version, Version::new(1, 2, 0));
+
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+    }
+}
@@ -1,5 +1,4 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::path::PathBuf;
 use semver::{Version, VersionReq};
 
 use crate::dependency::types::{
@@ -10,6 +9,7 @@ use crate::dependency::types::{
 use crate::dependency::error::{ResolutionError, ResolutionResult};
 use crate::dependency::registry::{PackageRegistry, LocalPackageRegistry, RemotePackageRegistry};
 use crate::dependency::cache::ResolutionCache;
+use crate::dependency::fingerprint;
 
 pub struct DependencyResolver {
     context: ResolutionContext,
@@ -23,13 +23,13 @@ impl DependencyResolver {
         context: ResolutionContext,
         local_registry: LocalPackageRegistry,
         remote_registry: RemotePackageRegistry,
-        cache_dir: PathBuf,
+        cache: ResolutionCache,
     ) -> Self {
         Self {
             context,
             local_registry,
             remote_registry,
-            cache: ResolutionCache::new(cache_dir),
+            cache,
         }
     }
 
@@ -42,6 +42,10 @@ impl DependencyResolver {
             context: self.context.clone(),
             overrides: HashMap::new(),
             excludes: Vec::new(),
+            fingerprints: fingerprint::packages_fingerprints(
+                self.local_registry.packages_path(),
+                self.cache.fingerprint_sources(),
+            ),
         };
 
         // Check cache first
@@ -88,6 +92,7 @@ impl DependencyResolver {
     ) -> ResolutionResult<()> {
         let mut queue = VecDeque::from(root_deps.to_vec());
         let mut discovered = HashSet::new();
+        let mut missing_offline = Vec::new();
 
         // Discover local packages
         self.local_registry.discover_packages().await?;
@@ -102,8 +107,16 @@ impl DependencyResolver {
             }
             discovered.insert(dep_spec.id.clone());
 
-            // Discover available versions for this package
-            let versions = self.discover_package_versions(&dep_spec.id).await?;
+            // Discover available versions for this package, collecting every package
+            // missing from the offline cache so they can be reported together
+            let versions = match self.discover_package_versions(&dep_spec.id).await {
+                Ok(versions) => versions,
+                Err(ResolutionError::OfflineUnavailable { packages }) => {
+                    missing_offline.extend(packages);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
             
             if versions.is_empty() {
                 return Err(ResolutionError::package_not_found(
@@ -130,6 +143,10 @@ impl DependencyResolver {
             }
         }
 
+        if !missing_offline.is_empty() {
+            return Err(ResolutionError::offline_unavailable(missing_offline));
+        }
+
         Ok(())
     }
 
@@ -398,11 +415,40 @@ impl DependencyResolver {
                 self.local_registry.list_versions(package_id).await
             }
             crate::dependency::types::PackageSource::Remote { .. } => {
-                self.remote_registry.list_versions(package_id).await
+                self.discover_remote_versions(package_id).await
             }
         }
     }
 
+    /// List remote versions from the on-disk metadata cache while it is fresh, otherwise from
+    /// the registry, falling back to the cache in offline mode or on network failures
+    async fn discover_remote_versions(&self, package_id: &PackageId) -> ResolutionResult<Vec<PackageVersion>> {
+        let registry_url = self.remote_registry.base_url();
+        if !crate::utils::is_offline() {
+            if let Some(versions) = self.cache.get_cached_package_versions(package_id, registry_url, false).await {
+                return Ok(versions);
+            }
+            match self.remote_registry.list_versions(package_id).await {
+                Ok(versions) => {
+                    if !versions.is_empty() {
+                        self.cache.cache_package_versions(package_id, versions.clone(), registry_url).await?;
+                    }
+                    return Ok(versions);
+                }
+                Err(ResolutionError::NetworkError { error, .. }) => {
+                    eprintln!(
+                        "⚠️  Could not reach the registry for '{}' ({}), using cached metadata",
+                        package_id.name, error
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.cache.get_cached_package_versions(package_id, registry_url, true).await
+            .ok_or_else(|| ResolutionError::offline_unavailable(vec![package_id.clone()]))
+    }
+
     async fn find_similar_packages(&self, query: &str) -> Vec<String> {
         let mut similar = Vec::new();
 
@@ -411,9 +457,11 @@ impl DependencyResolver {
             similar.extend(local_matches);
         }
 
-        // Search remote packages
-        if let Ok(remote_matches) = self.remote_registry.search_packages(query).await {
-            similar.extend(remote_matches);
+        // Search remote packages (only local suggestions are available offline)
+        if !crate::utils::is_offline() {
+            if let Ok(remote_matches) = self.remote_registry.search_packages(query).await {
+                similar.extend(remote_matches);
+            }
         }
 
         // Use fuzzy matching to find similar names
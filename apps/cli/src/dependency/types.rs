@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
@@ -140,6 +140,8 @@ pub struct ResolutionRequest {
     pub context: ResolutionContext,
     pub overrides: HashMap<PackageId, Version>,
     pub excludes: Vec<PackageId>,
+    /// Fingerprints of local packages, so cached resolutions are invalidated when they change
+    pub fingerprints: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,11 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::io::Cursor;
 use std::env;
 use flate2::read::GzDecoder;
 use tar::Archive;
 
+use crate::staging::StagingDir;
+use crate::utils;
+
 // Helper function to get the Knot Space URL (same as in commands.rs)
 fn get_knot_space_url() -> String {
     env::var("KNOT_SPACE_URL").unwrap_or_else(|_| "https://knot-space-production.up.railway.app".to_string())
@@ -13,6 +16,71 @@ fn get_knot_space_url() -> String {
 
 pub struct PackageDownloader;
 
+/// Downloaded tarballs kept under ~/.knot/cache/tarballs so packages can be
+/// reinstalled without network access
+pub struct TarballCache {
+    root: PathBuf,
+}
+
+impl TarballCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The tarball cache shared by all projects of the current user
+    pub fn user() -> Self {
+        Self::new(utils::user_cache_dir().join("tarballs"))
+    }
+
+    fn package_dir(&self, package_name: &str) -> PathBuf {
+        self.root.join(package_name.trim_start_matches('@').replace('/', "__"))
+    }
+
+    fn tarball_path(&self, package_name: &str, version: &str) -> PathBuf {
+        self.package_dir(package_name).join(format!("{}.tar.gz", version))
+    }
+
+    pub fn read(&self, package_name: &str, version: &str) -> Option<Vec<u8>> {
+        fs::read(self.tarball_path(package_name, version)).ok()
+    }
+
+    pub fn write(&self, package_name: &str, version: &str, content: &[u8]) -> Result<()> {
+        let path = self.tarball_path(package_name, version);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create tarball cache directory: {}", parent.display()))?;
+        }
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write cached tarball: {}", path.display()))
+    }
+
+    /// Cached versions of a package, newest first
+    pub fn versions(&self, package_name: &str) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.package_dir(package_name)) else {
+            return Vec::new();
+        };
+
+        let mut versions: Vec<semver::Version> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                file_name
+                    .strip_suffix(".tar.gz")
+                    .and_then(|v| semver::Version::parse(v).ok())
+            })
+            .collect();
+        versions.sort_by(|a, b| b.cmp(a));
+        versions.into_iter().map(|v| v.to_string()).collect()
+    }
+
+    /// Find the cached version that best satisfies the request
+    pub fn find(&self, package_name: &str, requested_version: Option<&str>) -> Option<String> {
+        let versions = self.versions(package_name);
+        let available: Vec<&str> = versions.iter().map(|v| v.as_str()).collect();
+        PackageDownloader::select_version(&available, requested_version)
+    }
+}
+
 impl PackageDownloader {
     pub async fn download_package(package_spec: &str, destination: &Path) -> Result<()> {
         if package_spec.starts_with('@') {
@@ -22,7 +90,7 @@ impl PackageDownloader {
         }
     }
 
-    fn parse_package_spec(package_spec: &str) -> (String, Option<String>) {
+    pub fn parse_package_spec(package_spec: &str) -> (String, Option<String>) {
         if let Some(stripped) = package_spec.strip_prefix('@') {
             // Handle scoped packages like @hono-modules-loader@0.2.5
             if let Some(at_pos) = stripped.find('@') {
@@ -69,6 +137,78 @@ impl PackageDownloader {
     }
 
     async fn download_from_knot_space(package_name: &str, requested_version: Option<&str>, destination: &Path) -> Result<()> {
+        let (_version, content) = Self::fetch_package_tarball(package_name, requested_version).await?;
+
+        // Extract tar.gz content
+        Self::extract_tarball(&content, destination)?;
+
+        Ok(())
+    }
+
+    /// Fetch the tarball of a Knot Space package, returning the concrete version that was downloaded.
+    /// Tarballs are cached on disk and reused when offline or when Knot Space is unreachable.
+    pub async fn fetch_package_tarball(package_name: &str, requested_version: Option<&str>) -> Result<(String, Vec<u8>)> {
+        let cache = TarballCache::user();
+
+        // Published versions are immutable, so an exact version in the cache never needs the network
+        if let Some(version) = requested_version.filter(|v| semver::Version::parse(v).is_ok()) {
+            if let Some(content) = cache.read(package_name, version) {
+                return Ok((version.to_string(), content));
+            }
+        }
+
+        if utils::is_offline() {
+            return Self::fetch_cached_tarball(&cache, package_name, requested_version);
+        }
+
+        match Self::fetch_remote_tarball(package_name, requested_version).await {
+            Ok((version, content)) => {
+                if let Err(e) = cache.write(package_name, &version, &content) {
+                    eprintln!("⚠️  {}", e);
+                }
+                Ok((version, content))
+            }
+            Err(e) if Self::is_network_error(&e) => {
+                let cached = Self::fetch_cached_tarball(&cache, package_name, requested_version)
+                    .map_err(|_| e)?;
+                eprintln!(
+                    "⚠️  Knot Space is unreachable, using cached {}@{}",
+                    package_name, cached.0
+                );
+                Ok(cached)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether a package spec can be installed from the tarball cache
+    pub fn is_cached(package_spec: &str) -> bool {
+        let (package_name, version) = Self::parse_package_spec(package_spec);
+        TarballCache::user().find(&package_name, version.as_deref()).is_some()
+    }
+
+    fn fetch_cached_tarball(cache: &TarballCache, package_name: &str, requested_version: Option<&str>) -> Result<(String, Vec<u8>)> {
+        let version = cache.find(package_name, requested_version).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Package '{}@{}' is not in the offline cache\n💡 Run 'knot link' once with network access to cache it\n💡 Or vendor it with 'knot vendor'",
+                package_name,
+                requested_version.unwrap_or("latest")
+            )
+        })?;
+        let content = cache
+            .read(package_name, &version)
+            .ok_or_else(|| anyhow::anyhow!("Failed to read cached tarball for {}@{}", package_name, version))?;
+        Ok((version, content))
+    }
+
+    fn is_network_error(error: &anyhow::Error) -> bool {
+        error
+            .downcast_ref::<reqwest::Error>()
+            .map(|e| e.is_connect() || e.is_timeout() || e.is_request())
+            .unwrap_or(false)
+    }
+
+    async fn fetch_remote_tarball(package_name: &str, requested_version: Option<&str>) -> Result<(String, Vec<u8>)> {
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(30))
             .connect_timeout(std::time::Duration::from_secs(10))
@@ -84,9 +224,9 @@ impl PackageDownloader {
         
         // Determine which version to download
         let version_to_download = match requested_version {
-            Some(version) if version != "latest" => version.to_string(),
+            Some(version) if version != "latest" && semver::Version::parse(version).is_ok() => version.to_string(),
             _ => {
-                // Fetch latest version
+                // Fetch available versions (newest first) and pick the best match
                 let versions_url = format!("{}/api/packages/{}/versions", base_url, api_package_name);
                 let versions_response = client.get(&versions_url).send().await?;
 
@@ -105,10 +245,18 @@ impl PackageDownloader {
                     anyhow::bail!("No versions found for package '{}' in Knot Space\n💡 The package exists but has no published versions\n💡 Contact the package maintainer to publish a version\n💡 Check if the package is still in development", package_name);
                 }
 
-                versions[0]["version"]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Invalid version format"))?
-                    .to_string()
+                let available: Vec<&str> = versions
+                    .iter()
+                    .filter_map(|v| v["version"].as_str())
+                    .collect();
+
+                Self::select_version(&available, requested_version)
+                    .ok_or_else(|| anyhow::anyhow!(
+                        "No version of '{}' matches '{}'\n💡 Available versions: {}",
+                        package_name,
+                        requested_version.unwrap_or("latest"),
+                        available.join(", ")
+                    ))?
             }
         };
         
@@ -124,18 +272,28 @@ impl PackageDownloader {
         
         // Download the tar.gz file
         let content = download_response.bytes().await?;
-        
-        // Create destination directory
-        fs::create_dir_all(destination)?;
-        
-        // Extract tar.gz content
-        Self::extract_tarball(&content, destination)?;
-        
-        Ok(())
+
+        Ok((version_to_download, content.to_vec()))
     }
 
+    /// Pick a version from a newest-first list: the first entry for `latest`,
+    /// otherwise the highest version satisfying the requested range
+    fn select_version(available: &[&str], requested_version: Option<&str>) -> Option<String> {
+        match requested_version {
+            None | Some("latest") => available.first().map(|v| v.to_string()),
+            Some(range) => {
+                let req = semver::VersionReq::parse(range).ok()?;
+                available
+                    .iter()
+                    .filter_map(|v| semver::Version::parse(v).ok())
+                    .filter(|v| req.matches(v))
+                    .max()
+                    .map(|v| v.to_string())
+            }
+        }
+    }
 
-    fn extract_tarball(content: &[u8], destination: &Path) -> Result<()> {
+    pub fn extract_tarball(content: &[u8], destination: &Path) -> Result<()> {
         // Create a cursor from the bytes
         let cursor = Cursor::new(content);
         
@@ -145,11 +303,36 @@ impl PackageDownloader {
         // Create a tar archive
         let mut archive = Archive::new(decoder);
         
-        // Extract all files to the destination
-        archive.unpack(destination)?;
+        // Extract next to the destination, so a failed or interrupted extraction leaves no partial package
+        let staging = StagingDir::new(destination)?;
+        archive.unpack(staging.path())?;
+        staging.commit()?;
         
         println!("📦 Extracted package contents to: {}", destination.display());
         Ok(())
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tarball_cache_finds_best_cached_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = TarballCache::new(temp_dir.path().to_path_buf());
+
+        for version in ["1.0.0", "1.2.0", "2.0.0"] {
+            cache.write("@team/utils", version, version.as_bytes()).unwrap();
+        }
+
+        assert_eq!(cache.versions("@team/utils"), vec!["2.0.0", "1.2.0", "1.0.0"]);
+        assert_eq!(cache.find("@team/utils", None).as_deref(), Some("2.0.0"));
+        assert_eq!(cache.find("@team/utils", Some("^1.0.0")).as_deref(), Some("1.2.0"));
+        assert_eq!(cache.find("@team/utils", Some("^3.0.0")), None);
+        assert_eq!(cache.read("@team/utils", "1.0.0").unwrap(), b"1.0.0");
+        assert!(cache.find("@team/other", None).is_none());
+    }
+}
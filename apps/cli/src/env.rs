@@ -0,0 +1,350 @@
+use anyhow::{Context, Result};
+use glob::Pattern;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Script;
+use crate::graph::ProjectNode;
+use crate::project::Project;
+use crate::utils;
+
+/// Injected variables that differ between machines or runs, left out of task cache hashes
+const VOLATILE_VARIABLES: [&str; 6] = [
+    "KNOT_PROJECT_ROOT",
+    "KNOT_PACKAGES_DIR",
+    "KNOT_VAR_PROJECT_ROOT",
+    "KNOT_VAR_TIMESTAMP",
+    "KNOT_VAR_DATE",
+    "KNOT_VAR_YEAR",
+];
+
+/// Profile selecting the `.env.<profile>` files, set by the global `--env-profile` flag or
+/// `KNOT_ENV_PROFILE`
+pub fn profile() -> Option<String> {
+    utils::global_options()
+        .env_profile
+        .clone()
+        .or_else(|| std::env::var("KNOT_ENV_PROFILE").ok())
+        .filter(|profile| !profile.is_empty())
+}
+
+/// Extra env files given with the global `--env-file` flag
+pub fn env_files() -> Vec<PathBuf> {
+    utils::global_options().env_files.clone()
+}
+
+/// Whether a variable is left out of task cache hashes
+pub fn is_volatile(key: &str) -> bool {
+    VOLATILE_VARIABLES.contains(&key)
+}
+
+/// Where a variable a script sees comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvOrigin {
+    /// knot's own environment, inherited by scripts without `env_passthrough`
+    Inherited,
+    /// knot's own environment, allowed by the script's `env_passthrough`
+    Passthrough,
+    /// `KNOT_*` variables describing the project, app or package
+    Injected,
+    /// A `.env` file or a file passed with `--env-file`
+    File(PathBuf),
+    /// The script's own `env`
+    Script,
+}
+
+/// Environment added to a script defined in `dir`, the directory of `node` (or the project root
+/// for project scripts): every variable of [`explain_env`] not inherited as is. Scripts with
+/// `env_passthrough` run with this environment alone.
+pub fn script_env(
+    project: Option<&Project>,
+    node: Option<&ProjectNode>,
+    dir: &Path,
+    script: &Script,
+) -> Result<BTreeMap<String, String>> {
+    Ok(explain_env(project, node, dir, script)?
+        .into_iter()
+        .filter(|(_, (_, origin))| *origin != EnvOrigin::Inherited)
+        .map(|(key, (value, _))| (key, value))
+        .collect())
+}
+
+/// Every variable the script sees, with where its value comes from. Later layers override
+/// earlier ones:
+///
+/// 1. knot's environment: all of it, or only the variables matching the script's
+///    `env_passthrough`
+/// 2. `KNOT_*` variables describing the project, app or package, and every resolved variable
+///    as `KNOT_VAR_<NAME>`
+/// 3. `.env` and `.env.<profile>` in the project root
+/// 4. `.env` and `.env.<profile>` in `dir`
+/// 5. the script's own `env`
+/// 6. files passed with `--env-file`
+pub fn explain_env(
+    project: Option<&Project>,
+    node: Option<&ProjectNode>,
+    dir: &Path,
+    script: &Script,
+) -> Result<BTreeMap<String, (String, EnvOrigin)>> {
+    let mut env = BTreeMap::new();
+    let mut set = |variables: Vec<(String, String)>, origin: EnvOrigin| {
+        for (key, value) in variables {
+            env.insert(key, (value, origin.clone()));
+        }
+    };
+
+    match script.env_passthrough() {
+        Some(allowed) => {
+            let patterns = allowed
+                .iter()
+                .map(|pattern| {
+                    Pattern::new(pattern).map_err(|e| {
+                        anyhow::anyhow!(
+                            "Invalid env_passthrough entry '{}': {}\n💡 List variable names like PATH, or globs like 'NPM_CONFIG_*'",
+                            pattern, e
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let passed = std::env::vars().filter(|(key, _)| patterns.iter().any(|pattern| pattern.matches(key)));
+            set(passed.collect(), EnvOrigin::Passthrough);
+        }
+        None => set(std::env::vars().collect(), EnvOrigin::Inherited),
+    }
+
+    let mut dirs = Vec::new();
+    if let Some(project) = project {
+        set(injected_variables(project, node, dir).into_iter().collect(), EnvOrigin::Injected);
+        dirs.push(project.root.as_path());
+    }
+    if !dirs.iter().any(|root| same_dir(root, dir)) {
+        dirs.push(dir);
+    }
+
+    let profile = profile();
+    for dir in dirs {
+        let mut files = vec![dir.join(".env")];
+        files.extend(profile.as_ref().map(|profile| dir.join(format!(".env.{}", profile))));
+        for file in files {
+            set(load_if_exists(&file)?, EnvOrigin::File(file));
+        }
+    }
+
+    set(
+        script.env().into_iter().flatten().map(|(key, value)| (key.clone(), value.clone())).collect(),
+        EnvOrigin::Script,
+    );
+
+    for file in env_files() {
+        if !file.is_file() {
+            anyhow::bail!(
+                "Env file not found: {}\n💡 Check the path passed to --env-file",
+                file.display()
+            );
+        }
+        set(load(&file)?, EnvOrigin::File(file));
+    }
+
+    Ok(env)
+}
+
+fn injected_variables(project: &Project, node: Option<&ProjectNode>, dir: &Path) -> BTreeMap<String, String> {
+    let mut env = BTreeMap::new();
+    env.insert("KNOT_PROJECT_ROOT".to_string(), project.root.display().to_string());
+    env.insert("KNOT_PROJECT_NAME".to_string(), project.config.name.clone());
+
+    let context = match node {
+        Some(ProjectNode::App(app)) => {
+            let name = project.apps.get(app).map_or(app.as_str(), |config| config.name.as_str());
+            env.insert("KNOT_APP_NAME".to_string(), name.to_string());
+            env.insert("KNOT_PACKAGES_DIR".to_string(), dir.join("knot_packages").display().to_string());
+            project.get_app_variable_context(app)
+        }
+        Some(ProjectNode::Package(package)) => {
+            let name = project.packages.get(package).map_or(package.as_str(), |config| config.name.as_str());
+            env.insert("KNOT_PACKAGE_NAME".to_string(), name.to_string());
+            project.get_package_variable_context(package)
+        }
+        None => project.variable_context.clone(),
+    };
+
+    for variable in context.list_variables() {
+        env.insert(format!("KNOT_VAR_{}", env_name(&variable.name)), variable.value);
+    }
+    env
+}
+
+/// `api-url` → `API_URL`
+fn env_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+fn load_if_exists(path: &Path) -> Result<Vec<(String, String)>> {
+    if path.is_file() {
+        load(path)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn load(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("Invalid env file {}", path.display()))
+}
+
+/// Parse `KEY=value` lines of a `.env` file. Supports comments, an `export` prefix, single
+/// quoted (literal) and double quoted (with `\n`, `\t`, `\"` and `\\` escapes) values, and
+/// ` #` comments after unquoted values.
+pub fn parse(content: &str) -> Result<Vec<(String, String)>> {
+    let mut variables = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+
+        let Some((key, value)) = line.split_once('=') else {
+            anyhow::bail!(
+                "Line {}: expected KEY=value, found '{}'\n💡 Comment lines start with '#'",
+                index + 1, line
+            );
+        };
+        let key = key.trim();
+        let valid_key = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        if !valid_key {
+            anyhow::bail!(
+                "Line {}: invalid variable name '{}'\n💡 Names start with a letter or '_' and contain letters, digits, '_' or '.'",
+                index + 1, key
+            );
+        }
+
+        let value = parse_value(value.trim()).ok_or_else(|| {
+            anyhow::anyhow!("Line {}: unterminated quoted value for '{}'\n💡 Close the quote on the same line", index + 1, key)
+        })?;
+        variables.push((key.to_string(), value));
+    }
+
+    Ok(variables)
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        return rest.find('\'').map(|end| rest[..end].to_string());
+    }
+
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Some(parsed),
+                '\\' => match chars.next()? {
+                    'n' => parsed.push('\n'),
+                    't' => parsed.push('\t'),
+                    'r' => parsed.push('\r'),
+                    other => parsed.push(other),
+                },
+                c => parsed.push(c),
+            }
+        }
+        return None;
+    }
+
+    let value = match value.find(" #") {
+        Some(comment) => &value[..comment],
+        None => value,
+    };
+    Some(value.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_env_file() {
+        let content = r#"
+# Database
+export DATABASE_URL=postgres://localhost/dev
+PORT = 3000 # inline comment
+GREETING="Hello \"knot\"\nBye"
+LITERAL='$HOME # not a comment'
+EMPTY=
+"#;
+        let variables = parse(content).unwrap();
+        assert_eq!(
+            variables,
+            vec![
+                ("DATABASE_URL".to_string(), "postgres://localhost/dev".to_string()),
+                ("PORT".to_string(), "3000".to_string()),
+                ("GREETING".to_string(), "Hello \"knot\"\nBye".to_string()),
+                ("LITERAL".to_string(), "$HOME # not a comment".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+
+        assert!(parse("NO_EQUALS").unwrap_err().to_string().contains("Line 1"));
+        assert!(parse("1BAD=value").is_err());
+        assert!(parse("OPEN=\"unterminated").is_err());
+    }
+
+    #[test]
+    fn test_script_env_layers_files_and_variables() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("knot.yml", "name: demo\nvariables:\n  api-url: http://localhost\n");
+        write("apps/web/app.yml", "name: web-app\nscripts:\n  dev:\n    run: vite\n    env:\n      FROM_SCRIPT: script\n");
+        write(".env", "SHARED=root\nROOT_ONLY=root\nFROM_SCRIPT=root\n");
+        write("apps/web/.env", "SHARED=app\n");
+
+        let project = Project::find_and_load(root).unwrap();
+        let node = ProjectNode::App("web".to_string());
+        let dir = node.dir(&project.root);
+        let script = project.apps["web"].scripts.as_ref().unwrap()["dev"].clone();
+        let env = script_env(Some(&project), Some(&node), &dir, &script).unwrap();
+
+        assert_eq!(env["SHARED"], "app");
+        assert_eq!(env["ROOT_ONLY"], "root");
+        assert_eq!(env["FROM_SCRIPT"], "script");
+        assert_eq!(env["KNOT_APP_NAME"], "web-app");
+        assert_eq!(env["KNOT_PROJECT_NAME"], "demo");
+        assert_eq!(env["KNOT_VAR_API_URL"], "http://localhost");
+        assert_eq!(env["KNOT_PACKAGES_DIR"], dir.join("knot_packages").display().to_string());
+    }
+
+    #[test]
+    fn test_env_passthrough_keeps_only_allowed_variables() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".env"), "CARGO_PKG_NAME=from-file\n").unwrap();
+        let script: Script = serde_yaml::from_str("run: make\nenv_passthrough: [PATH, 'CARGO_PKG_*']\n").unwrap();
+
+        // Set by cargo for test binaries
+        let env = explain_env(None, None, temp_dir.path(), &script).unwrap();
+        assert_eq!(env["PATH"].1, EnvOrigin::Passthrough);
+        assert_eq!(env["CARGO_PKG_VERSION"].1, EnvOrigin::Passthrough);
+        assert_eq!(env["CARGO_PKG_NAME"], ("from-file".to_string(), EnvOrigin::File(temp_dir.path().join(".env"))));
+        assert!(!env.contains_key("CARGO_MANIFEST_DIR"));
+
+        // Without an allowlist everything is inherited, but only knot's own layers are added
+        let script = Script::Command("make".to_string());
+        assert_eq!(explain_env(None, None, temp_dir.path(), &script).unwrap()["CARGO_MANIFEST_DIR"].1, EnvOrigin::Inherited);
+        let env = script_env(None, None, temp_dir.path(), &script).unwrap();
+        assert_eq!(env.keys().collect::<Vec<_>>(), vec!["CARGO_PKG_NAME"]);
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::Result;
+use glob::Pattern;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::affected;
+use crate::graph::{ProjectGraph, ProjectNode};
+use crate::project::Project;
+
+/// Selection of apps and packages from `--filter` expressions, shared by every command
+/// working on several projects. An expression is one of
+///
+/// - `web`, `ui-*`: name glob
+/// - `apps`, `packages`: every app or package
+/// - `./apps/**`: directory glob, relative to the project root
+/// - `tag:ui`: packages with a matching tag
+/// - `[origin/main]`: projects with files changed since the git ref
+///
+/// optionally extended with `...utils` (plus its dependents), `web...` (plus its
+/// dependencies) or `^` to leave the matched projects out (`...^utils`, `web^...`), and
+/// negated with a leading `!`. Projects matching any expression and no negated one are
+/// selected; with only negated expressions, everything else is.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Selector {
+    exclude: bool,
+    matcher: Matcher,
+    dependents: Expansion,
+    dependencies: Expansion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expansion {
+    None,
+    /// The matched projects and those reached from them
+    Including,
+    /// Only the projects reached from the matched ones
+    Only,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Matcher {
+    Name(Pattern),
+    Kind(&'static str),
+    Dir(Pattern),
+    Tag(Pattern),
+    ChangedSince(String),
+}
+
+impl Filter {
+    pub fn parse(expressions: &[String]) -> Result<Self> {
+        let selectors = expressions
+            .iter()
+            .map(|expression| Selector::parse(expression))
+            .collect::<Result<_>>()?;
+        Ok(Self { selectors })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
+
+    /// The selected apps and packages; all of them without expressions
+    pub fn select(&self, project: &Project, graph: &ProjectGraph) -> Result<BTreeSet<ProjectNode>> {
+        let (excluded, included): (Vec<&Selector>, Vec<&Selector>) =
+            self.selectors.iter().partition(|selector| selector.exclude);
+
+        let mut selected = if included.is_empty() {
+            graph.all_nodes()
+        } else {
+            let mut selected = BTreeSet::new();
+            for selector in included {
+                selected.extend(selector.select(project, graph)?);
+            }
+            selected
+        };
+        for selector in excluded {
+            for node in selector.select(project, graph)? {
+                selected.remove(&node);
+            }
+        }
+        Ok(selected)
+    }
+}
+
+impl Selector {
+    fn parse(expression: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            anyhow::anyhow!(
+                "Invalid filter '{}': {}\n💡 Examples: --filter 'web*', --filter apps, --filter ...utils, --filter web..., --filter './packages/**', --filter tag:ui, --filter '[origin/main]', --filter '!docs'",
+                expression, reason
+            )
+        };
+
+        let mut rest = expression.trim();
+        let exclude = match rest.strip_prefix('!') {
+            Some(negated) => {
+                rest = negated;
+                true
+            }
+            None => false,
+        };
+
+        let mut dependents = Expansion::None;
+        if let Some(core) = rest.strip_prefix("...") {
+            rest = core;
+            dependents = Expansion::Including;
+            if let Some(core) = rest.strip_prefix('^') {
+                rest = core;
+                dependents = Expansion::Only;
+            }
+        }
+        let mut dependencies = Expansion::None;
+        if let Some(core) = rest.strip_suffix("...") {
+            rest = core;
+            dependencies = Expansion::Including;
+            if let Some(core) = rest.strip_suffix('^') {
+                rest = core;
+                dependencies = Expansion::Only;
+            }
+        }
+
+        let pattern = |glob: &str| Pattern::new(glob).map_err(|e| invalid(&e.to_string()));
+        let matcher = if rest.is_empty() {
+            return Err(invalid("nothing to match"));
+        } else if let Some(git_ref) = rest.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            if git_ref.trim().is_empty() {
+                return Err(invalid("empty git ref"));
+            }
+            Matcher::ChangedSince(git_ref.trim().to_string())
+        } else if let Some(tag) = rest.strip_prefix("tag:") {
+            Matcher::Tag(pattern(tag)?)
+        } else if let Some(dir) = rest.strip_prefix("./") {
+            Matcher::Dir(pattern(dir.trim_end_matches('/'))?)
+        } else if rest == "apps" {
+            Matcher::Kind("app")
+        } else if rest == "packages" {
+            Matcher::Kind("package")
+        } else {
+            Matcher::Name(pattern(rest)?)
+        };
+
+        Ok(Self {
+            exclude,
+            matcher,
+            dependents,
+            dependencies,
+        })
+    }
+
+    fn select(&self, project: &Project, graph: &ProjectGraph) -> Result<BTreeSet<ProjectNode>> {
+        let matched: BTreeSet<ProjectNode> = match &self.matcher {
+            Matcher::ChangedSince(git_ref) => {
+                let changed_files = affected::changed_files(&project.root, git_ref)?;
+                affected::changed_nodes(project, graph, &changed_files)
+            }
+            matcher => graph
+                .all_nodes()
+                .into_iter()
+                .filter(|node| matcher.matches(project, node))
+                .collect(),
+        };
+
+        let mut selected = BTreeSet::new();
+        if self.dependents != Expansion::Only && self.dependencies != Expansion::Only {
+            selected.extend(matched.iter().cloned());
+        }
+        for node in &matched {
+            let roots = std::slice::from_ref(node);
+            if self.dependents != Expansion::None {
+                selected.extend(graph.with_dependents(roots).into_iter().filter(|other| other != node));
+            }
+            if self.dependencies != Expansion::None {
+                selected.extend(graph.with_dependencies(roots).into_iter().filter(|other| other != node));
+            }
+        }
+        Ok(selected)
+    }
+}
+
+impl Matcher {
+    fn matches(&self, project: &Project, node: &ProjectNode) -> bool {
+        match self {
+            Matcher::Name(pattern) => pattern.matches(node.name()) || config_name(project, node).is_some_and(|name| pattern.matches(name)),
+            Matcher::Kind(kind) => node.kind() == *kind,
+            Matcher::Dir(pattern) => {
+                let dir = node.dir(Path::new(""));
+                pattern.matches(&dir.to_string_lossy().replace('\\', "/"))
+            }
+            Matcher::Tag(pattern) => match node {
+                ProjectNode::Package(package) => project
+                    .packages
+                    .get(package)
+                    .and_then(|config| config.tags.as_ref())
+                    .is_some_and(|tags| tags.iter().any(|tag| pattern.matches(tag))),
+                ProjectNode::App(_) => false,
+            },
+            Matcher::ChangedSince(_) => false,
+        }
+    }
+}
+
+/// Name from the app.yml or package.yml, which may differ from the directory name
+fn config_name<'a>(project: &'a Project, node: &ProjectNode) -> Option<&'a str> {
+    match node {
+        ProjectNode::App(app) => project.apps.get(app).map(|config| config.name.as_str()),
+        ProjectNode::Package(package) => project.packages.get(package).map(|config| config.name.as_str()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn select(project: &Project, expressions: &[&str]) -> Vec<String> {
+        let expressions: Vec<String> = expressions.iter().map(|e| e.to_string()).collect();
+        let graph = ProjectGraph::from_project(project);
+        Filter::parse(&expressions)
+            .unwrap()
+            .select(project, &graph)
+            .unwrap()
+            .iter()
+            .map(|node| node.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_expressions() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "knot.yml", "name: demo\n");
+        write(root, "packages/types/package.yml", "name: types\nversion: 1.0.0\ntags: [core]\n");
+        write(root, "packages/utils/package.yml", "name: utils\nversion: 1.0.0\ndependencies:\n  - types\n");
+        write(root, "packages/ui-kit/package.yml", "name: ui-kit\nversion: 1.0.0\ntags: [ui]\n");
+        write(root, "apps/web/app.yml", "name: web\npackages:\n  - utils\n  - ui-kit\n");
+        write(root, "apps/docs/app.yml", "name: docs\n");
+        let project = Project::find_and_load(root).unwrap();
+
+        assert_eq!(select(&project, &[]).len(), 5);
+        assert_eq!(select(&project, &["u*"]), vec!["ui-kit", "utils"]);
+        assert_eq!(select(&project, &["apps"]), vec!["docs", "web"]);
+        assert_eq!(select(&project, &["./packages/**", "!tag:ui"]), vec!["types", "utils"]);
+        assert_eq!(select(&project, &["tag:core"]), vec!["types"]);
+        assert_eq!(select(&project, &["...types"]), vec!["types", "utils", "web"]);
+        assert_eq!(select(&project, &["...^types"]), vec!["utils", "web"]);
+        assert_eq!(select(&project, &["web..."]), vec!["types", "ui-kit", "utils", "web"]);
+        assert_eq!(select(&project, &["web^..."]), vec!["types", "ui-kit", "utils"]);
+        assert_eq!(select(&project, &["!apps"]), vec!["types", "ui-kit", "utils"]);
+
+        assert!(Filter::parse(&["...".to_string()]).is_err());
+        assert!(Filter::parse(&["[]".to_string()]).is_err());
+    }
+}
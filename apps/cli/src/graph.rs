@@ -0,0 +1,235 @@
+use crate::project::Project;
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// An app or local package of the project
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProjectNode {
+    Package(String),
+    App(String),
+}
+
+impl ProjectNode {
+    pub fn name(&self) -> &str {
+        match self {
+            ProjectNode::Package(name) | ProjectNode::App(name) => name,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProjectNode::Package(_) => "package",
+            ProjectNode::App(_) => "app",
+        }
+    }
+
+    /// Directory of the app or package inside the project
+    pub fn dir(&self, project_root: &Path) -> PathBuf {
+        match self {
+            ProjectNode::Package(name) => project_root.join("packages").join(name),
+            ProjectNode::App(name) => project_root.join("apps").join(name),
+        }
+    }
+}
+
+impl fmt::Display for ProjectNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Dependency graph between apps and local packages. Knot Space packages
+/// (`@name`) are not part of the graph since they are never built locally.
+#[derive(Debug, Default)]
+pub struct ProjectGraph {
+    dependencies: BTreeMap<ProjectNode, BTreeSet<ProjectNode>>,
+}
+
+impl ProjectGraph {
+    pub fn from_project(project: &Project) -> Self {
+        let mut graph = Self::default();
+
+        for (package_dir, package_config) in &project.packages {
+            let node = ProjectNode::Package(package_dir.clone());
+            let deps = package_config
+                .dependencies
+                .iter()
+                .flatten()
+                .filter_map(|dep| Self::local_package(project, dep))
+                .collect();
+            graph.dependencies.insert(node, deps);
+        }
+
+        for app_name in project.get_app_names() {
+            let deps = project
+                .get_app_dependencies(&app_name)
+                .iter()
+                .filter_map(|dep| Self::local_package(project, dep))
+                .collect();
+            graph.dependencies.insert(ProjectNode::App(app_name), deps);
+        }
+
+        graph
+    }
+
+    #[cfg(test)]
+    pub fn from_edges(edges: &[(ProjectNode, &[ProjectNode])]) -> Self {
+        Self {
+            dependencies: edges
+                .iter()
+                .map(|(node, deps)| (node.clone(), deps.iter().cloned().collect()))
+                .collect(),
+        }
+    }
+
+    /// Map a dependency spec (`utils`, `utils@^1.0.0`) to the local package it refers to
+    fn local_package(project: &Project, dep: &str) -> Option<ProjectNode> {
+        if dep.starts_with('@') {
+            return None;
+        }
+        let name = dep.rfind('@').map_or(dep, |at| &dep[..at]);
+
+        if project.packages.contains_key(name) {
+            return Some(ProjectNode::Package(name.to_string()));
+        }
+        project
+            .packages
+            .iter()
+            .find(|(_, config)| config.name == name)
+            .map(|(dir, _)| ProjectNode::Package(dir.clone()))
+    }
+
+    pub fn all_nodes(&self) -> BTreeSet<ProjectNode> {
+        self.dependencies.keys().cloned().collect()
+    }
+
+    pub fn dependencies_of(&self, node: &ProjectNode) -> impl Iterator<Item = &ProjectNode> {
+        self.dependencies.get(node).into_iter().flatten()
+    }
+
+    /// The given nodes plus everything they depend on, transitively
+    pub fn with_dependencies(&self, roots: &[ProjectNode]) -> BTreeSet<ProjectNode> {
+        let mut selected = BTreeSet::new();
+        let mut stack: Vec<ProjectNode> = roots.to_vec();
+
+        while let Some(node) = stack.pop() {
+            if selected.insert(node.clone()) {
+                stack.extend(self.dependencies_of(&node).cloned());
+            }
+        }
+
+        selected
+    }
+
+    /// The given nodes plus everything that depends on them, transitively
+    pub fn with_dependents(&self, roots: &[ProjectNode]) -> BTreeSet<ProjectNode> {
+        let mut selected = BTreeSet::new();
+        let mut stack: Vec<ProjectNode> = roots.to_vec();
+
+        while let Some(node) = stack.pop() {
+            if selected.insert(node.clone()) {
+                stack.extend(
+                    self.dependencies
+                        .iter()
+                        .filter(|(_, deps)| deps.contains(&node))
+                        .map(|(dependent, _)| dependent.clone()),
+                );
+            }
+        }
+
+        selected
+    }
+
+    /// Order `nodes` so every node comes after its dependencies
+    pub fn topological_order(&self, nodes: &BTreeSet<ProjectNode>) -> Result<Vec<ProjectNode>> {
+        let mut remaining: BTreeMap<&ProjectNode, BTreeSet<&ProjectNode>> = nodes
+            .iter()
+            .map(|node| {
+                let deps = self.dependencies_of(node).filter(|dep| nodes.contains(*dep)).collect();
+                (node, deps)
+            })
+            .collect();
+        let mut order = Vec::with_capacity(nodes.len());
+
+        while !remaining.is_empty() {
+            let ready: Vec<&ProjectNode> = remaining
+                .iter()
+                .filter(|(_, deps)| deps.is_empty())
+                .map(|(node, _)| *node)
+                .collect();
+
+            if ready.is_empty() {
+                let cycle: Vec<String> = remaining.keys().map(|node| node.to_string()).collect();
+                anyhow::bail!(
+                    "Circular dependency between local packages: {}\n💡 Check the dependencies listed in their package.yml files",
+                    cycle.join(", ")
+                );
+            }
+
+            for node in ready {
+                remaining.remove(node);
+                for deps in remaining.values_mut() {
+                    deps.remove(node);
+                }
+                order.push(node.clone());
+            }
+        }
+
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str) -> ProjectNode {
+        ProjectNode::Package(name.to_string())
+    }
+
+    #[test]
+    fn test_topological_order_builds_dependencies_first() {
+        let web = ProjectNode::App("web".to_string());
+        let graph = ProjectGraph::from_edges(&[
+            (package("types"), &[]),
+            (package("utils"), &[package("types")]),
+            (package("unused"), &[]),
+            (web.clone(), &[package("utils")]),
+        ]);
+
+        let selected = graph.with_dependencies(std::slice::from_ref(&web));
+        assert!(!selected.contains(&package("unused")));
+
+        let order = graph.topological_order(&selected).unwrap();
+        assert_eq!(order, vec![package("types"), package("utils"), web]);
+    }
+
+    #[test]
+    fn test_with_dependents_follows_reverse_edges() {
+        let web = ProjectNode::App("web".to_string());
+        let docs = ProjectNode::App("docs".to_string());
+        let graph = ProjectGraph::from_edges(&[
+            (package("types"), &[]),
+            (package("utils"), &[package("types")]),
+            (web.clone(), &[package("utils")]),
+            (docs.clone(), &[]),
+        ]);
+
+        let affected = graph.with_dependents(&[package("types")]);
+        assert_eq!(affected, BTreeSet::from([package("types"), package("utils"), web]));
+        assert_eq!(graph.with_dependents(std::slice::from_ref(&docs)), BTreeSet::from([docs]));
+    }
+
+    #[test]
+    fn test_topological_order_detects_cycles() {
+        let graph = ProjectGraph::from_edges(&[
+            (package("a"), &[package("b")]),
+            (package("b"), &[package("a")]),
+        ]);
+
+        let error = graph.topological_order(&graph.all_nodes()).unwrap_err().to_string();
+        assert!(error.contains("a, b"));
+    }
+}
@@ -1,23 +1,31 @@
 use crate::downloader::PackageDownloader;
 use crate::project::Project;
+use crate::staging::StagingDir;
+use crate::vendor::VendorStore;
 use anyhow::{Context, Result};
 use std::fs;
 use std::path::Path;
 
 pub struct Linker<'a> {
     project: &'a Project,
+    offline: bool,
 }
 
 impl<'a> Linker<'a> {
     pub fn new(project: &'a Project) -> Self {
-        Self { project }
+        Self {
+            project,
+            offline: crate::utils::is_offline(),
+        }
     }
 
-    pub async fn link_all_apps(&self, use_symlinks: bool) -> Result<()> {
-        let app_names = self.project.get_app_names();
+    pub async fn link_apps(&self, app_names: &[String], use_symlinks: bool) -> Result<()> {
+        if self.offline {
+            self.ensure_available_offline(app_names)?;
+        }
 
         for app_name in app_names {
-            self.link_app(&app_name, use_symlinks).await?;
+            self.link_app(app_name, use_symlinks).await?;
         }
 
         Ok(())
@@ -31,16 +39,8 @@ impl<'a> Linker<'a> {
 
         let knot_packages_dir = app_dir.join("knot_packages");
 
-        if knot_packages_dir.exists() {
-            fs::remove_dir_all(&knot_packages_dir).with_context(|| {
-                format!(
-                    "Failed to remove existing knot_packages directory for app {}",
-                    app_name
-                )
-            })?;
-        }
-
-        fs::create_dir_all(&knot_packages_dir).with_context(|| {
+        // Linked into a staging directory so an interrupted link keeps the previous packages
+        let staging = StagingDir::new(&knot_packages_dir).with_context(|| {
             format!(
                 "Failed to create knot_packages directory for app {}",
                 app_name
@@ -50,10 +50,17 @@ impl<'a> Linker<'a> {
         let dependencies = self.project.get_app_dependencies(app_name);
 
         for dep in dependencies {
-            self.link_dependency(app_name, &dep, &knot_packages_dir, use_symlinks)
+            self.link_dependency(app_name, &dep, staging.path(), use_symlinks)
                 .await?;
         }
 
+        staging.commit().with_context(|| {
+            format!(
+                "Failed to replace the knot_packages directory for app {}",
+                app_name
+            )
+        })?;
+
         println!(
             "Linked {} dependencies for app '{}'",
             self.count_linked_packages(&knot_packages_dir)?,
@@ -73,6 +80,18 @@ impl<'a> Linker<'a> {
         if let Some(folder_name) = dependency.strip_prefix('@') {
             // Remove @ prefix for folder name, but keep it for package identification
             let link_target = knot_packages_dir.join(folder_name);
+            let vendor = VendorStore::new(&self.project.root);
+            if self.offline && vendor.find(dependency)?.is_some() {
+                vendor
+                    .install(dependency, &link_target)
+                    .with_context(|| {
+                        format!(
+                            "Failed to install vendored package '{}' for app '{}'",
+                            dependency, app_name
+                        )
+                    })?;
+                return Ok(());
+            }
             PackageDownloader::download_package(dependency, &link_target)
                 .await
                 .with_context(|| {
@@ -115,6 +134,34 @@ impl<'a> Linker<'a> {
         Ok(())
     }
 
+    /// Check every online dependency can be installed from vendor/knot or the
+    /// tarball cache, so all missing packages are reported at once
+    fn ensure_available_offline(&self, app_names: &[String]) -> Result<()> {
+        let vendor = VendorStore::new(&self.project.root);
+        let mut missing = Vec::new();
+
+        for app_name in app_names {
+            for dep in self.project.get_app_dependencies(app_name) {
+                if !dep.starts_with('@') || missing.contains(&dep) {
+                    continue;
+                }
+                if vendor.find(&dep)?.is_none() && !PackageDownloader::is_cached(&dep) {
+                    missing.push(dep);
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Cannot link offline: {} package(s) are neither vendored nor cached:\n{}\n💡 Run 'knot link' once with network access to cache them\n💡 Or run 'knot vendor' while online and commit vendor/knot",
+                missing.len(),
+                missing.iter().map(|dep| format!("  • {}", dep)).collect::<Vec<_>>().join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
     #[cfg(unix)]
     fn create_symlink(&self, source: &Path, target: &Path) -> Result<()> {
         std::os::unix::fs::symlink(source, target)?;
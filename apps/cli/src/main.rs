@@ -1,23 +1,51 @@
+mod affected;
 mod commands;
 mod config;
 mod dependency;
 mod downloader;
+mod env;
+mod filter;
+mod graph;
 mod ignore;
 mod interpolation;
 mod linker;
 mod project;
+mod staging;
+mod tasks;
 mod templates;
+mod toolchain;
 mod typescript;
 mod utils;
 mod validation;
 mod variables;
+mod vendor;
+mod watch;
 
 use anyhow::Result;
-use clap::{Arg, Command};
+use clap::{Arg, ArgMatches, Command};
 use console;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
+    let result = run().await;
+    // Scripts stopped by Ctrl+C end the run early; the signal handler exits once they are gone
+    if tasks::process::stopping() {
+        loop {
+            std::thread::park();
+        }
+    }
+    if let Err(e) = result {
+        // Printed like an error returned from main, but exiting with the status of failed scripts
+        eprintln!("Error: {:?}", e);
+        let code = e
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<tasks::ExitError>())
+            .map_or(1, |exit| exit.code);
+        std::process::exit(code);
+    }
+}
+
+async fn run() -> Result<()> {
     // Setup graceful Ctrl+C handling
     if let Err(_) = commands::common::setup_ctrl_c_handler() {
         // Silently ignore if we can't set up Ctrl+C handler
@@ -28,6 +56,35 @@ async fn main() -> Result<()> {
         .subcommand_required(false)
         .arg_required_else_help(false)
         .allow_external_subcommands(true)
+        .arg(
+            Arg::new("offline")
+                .help("Never use the network: resolve and install from vendor/knot and local caches")
+                .long("offline")
+                .global(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("ignore-scripts")
+                .help("Do not run pre<script>/post<script> hooks, e.g. prebuild, postlink or prepublish")
+                .long("ignore-scripts")
+                .global(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("env-profile")
+                .help("Also load .env.<profile> files into scripts, e.g. --env-profile staging")
+                .long("env-profile")
+                .value_name("NAME")
+                .global(true),
+        )
+        .arg(
+            Arg::new("env-file")
+                .help("Load an extra env file into scripts, overriding .env files (repeatable)")
+                .long("env-file")
+                .value_name("PATH")
+                .global(true)
+                .action(clap::ArgAction::Append),
+        )
         .subcommand(
             Command::new("init")
                 .about("Initialize a new Knot project")
@@ -134,7 +191,25 @@ async fn main() -> Result<()> {
                         .help("Use symlinks instead of copying (default: false)")
                         .long("symlink")
                         .action(clap::ArgAction::SetTrue),
-                ),
+                )
+                .arg(filter_arg()),
+        )
+        .subcommand(
+            Command::new("build")
+                .alias("b")
+                .about("Build apps after the local packages they depend on (context-aware)")
+                .arg(
+                    Arg::new("apps")
+                        .help("Apps to build (default: current app, or all apps with a build command)")
+                        .num_args(0..)
+                        .index(1),
+                )
+                .arg(filter_arg().conflicts_with("apps"))
+                .args(scheduler_args()),
+        )
+        .subcommand(
+            Command::new("vendor")
+                .about("Download every Knot Space package used by apps into vendor/knot")
         )
         .subcommand(
             Command::new("run")
@@ -145,9 +220,101 @@ async fn main() -> Result<()> {
                         .help("Script name to run (optional - will show interactive selection if omitted)")
                         .required(false)
                         .index(1),
+                )
+                .arg(
+                    Arg::new("all")
+                        .help("Run the script in every app and package that defines it")
+                        .long("all")
+                        .requires("script")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("affected")
+                        .help("Run the script only in apps and packages affected by git changes (implies --all)")
+                        .long("affected")
+                        .requires("script")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(since_arg().requires("affected"))
+                .arg(filter_arg().requires("script"))
+                .args(scheduler_args())
+                .arg(
+                    Arg::new("watch")
+                        .help("Re-run the script when files of the app or package, or of its local packages, change")
+                        .long("watch")
+                        .short('w')
+                        .requires("script")
+                        .conflicts_with_all(["all", "affected", "filter"])
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("explain-env")
+                        .help("Show the environment variables the script would see and where each comes from, without running it")
+                        .long("explain-env")
+                        .requires("script")
+                        .conflicts_with_all(["all", "affected", "filter", "watch"])
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("args")
+                        .help("Arguments passed to the script, after -- (e.g. knot run test -- --watch)")
+                        .index(2)
+                        .num_args(0..)
+                        .last(true)
+                        .allow_hyphen_values(true),
+                ),
+        )
+        .subcommand(
+            Command::new("exec")
+                .about("Run a command in every app and package, or those matching --filter")
+                .arg(filter_arg())
+                .args(scheduler_args())
+                .arg(
+                    Arg::new("command")
+                        .help("Command to run, after -- (e.g. knot exec -- rm -rf dist)")
+                        .required(true)
+                        .num_args(1..)
+                        .last(true)
+                        .allow_hyphen_values(true),
+                ),
+        )
+        .subcommand(
+            Command::new("dev")
+                .about("Start the dev script of apps concurrently, following their dev.depends_on")
+                .arg(
+                    Arg::new("apps")
+                        .help("Apps to start (defaults to the current app, or every app with a dev script)")
+                        .num_args(0..)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            Command::new("affected")
+                .about("List apps and packages affected by git changes, including their dependents")
+                .arg(since_arg())
+                .arg(
+                    Arg::new("json")
+                        .help("Print the list as JSON")
+                        .long("json")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("graph")
+                .about("Show apps and packages, or those matching --filter, with the local packages they depend on")
+                .arg(filter_arg())
+                .arg(
+                    Arg::new("json")
+                        .help("Print the graph as JSON")
+                        .long("json")
+                        .action(clap::ArgAction::SetTrue),
                 ),
         )
         .subcommand(Command::new("status").alias("s").about("Show project status"))
+        .subcommand(
+            Command::new("doctor")
+                .about("Check installed tools against the versions required by knot.yml and app.yml"),
+        )
         .subcommand(
             Command::new("auth")
                 .alias("a")
@@ -473,6 +640,34 @@ async fn main() -> Result<()> {
                         ),
                 ),
         )
+        .subcommand(
+            Command::new("cache")
+                .about("Dependency resolution and task cache management")
+                .subcommand_required(true)
+                .subcommand(Command::new("stats").about("Show resolution cache statistics"))
+                .subcommand(Command::new("clear").about("Remove all cached resolutions"))
+                .subcommand(Command::new("gc").about("Remove cache entries older than the configured TTL"))
+                .subcommand(
+                    Command::new("clean")
+                        .about("Remove cached resolutions and task outputs")
+                        .arg(
+                            Arg::new("tasks")
+                                .help("Only remove cached task outputs and logs")
+                                .long("tasks")
+                                .action(clap::ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(
+                    Command::new("invalidate")
+                        .about("Remove cached resolutions that include a package")
+                        .arg(
+                            Arg::new("package")
+                                .help("Package name")
+                                .required(true)
+                                .index(1),
+                        ),
+                ),
+        )
         .subcommand(
             Command::new("upgrade")
                 .alias("u")
@@ -486,6 +681,21 @@ async fn main() -> Result<()> {
         )
         .get_matches();
 
+    let env_files = match matches.get_many::<String>("env-file") {
+        Some(files) => {
+            // Relative to where knot was started, as scripts run in other directories
+            let current_dir = std::env::current_dir()?;
+            files.map(|file| current_dir.join(file)).collect()
+        }
+        None => Vec::new(),
+    };
+    utils::set_global_options(utils::GlobalOptions {
+        offline: matches.get_flag("offline"),
+        ignore_scripts: matches.get_flag("ignore-scripts"),
+        env_profile: matches.get_one::<String>("env-profile").cloned(),
+        env_files,
+    });
+
     match matches.subcommand() {
         Some(("init", sub_matches)) => {
             let name = sub_matches.get_one::<String>("name");
@@ -519,18 +729,57 @@ async fn main() -> Result<()> {
         }
         Some(("link", sub_matches)) => {
             let use_symlinks = sub_matches.get_flag("symlink");
-            commands::link_packages(use_symlinks).await?;
+            commands::link_packages(use_symlinks, &filter(sub_matches)?).await?;
+        }
+        Some(("build", sub_matches)) => {
+            let app_names: Vec<String> = sub_matches
+                .get_many::<String>("apps")
+                .map(|apps| apps.cloned().collect())
+                .unwrap_or_default();
+            commands::build_apps(&app_names, &filter(sub_matches)?, &scheduler_options(sub_matches)?).await?;
+        }
+        Some(("vendor", _)) => {
+            commands::vendor_packages().await?;
         }
         Some(("run", sub_matches)) => {
             if let Some(script_name) = sub_matches.get_one::<String>("script") {
-                commands::run_script(script_name).await?;
+                let args: Vec<String> = sub_matches.get_many::<String>("args").into_iter().flatten().cloned().collect();
+                if sub_matches.get_flag("affected") {
+                    let since = sub_matches.get_one::<String>("since").map_or(DEFAULT_SINCE, |s| s.as_str());
+                    commands::run_script_all(script_name, &args, Some(since), &filter(sub_matches)?, &scheduler_options(sub_matches)?).await?;
+                } else if sub_matches.get_flag("all") || sub_matches.contains_id("filter") {
+                    commands::run_script_all(script_name, &args, None, &filter(sub_matches)?, &scheduler_options(sub_matches)?).await?;
+                } else {
+                    commands::run_script(script_name, &args, sub_matches.get_flag("watch"), sub_matches.get_flag("explain-env")).await?;
+                }
             } else {
                 commands::run_script_interactive().await?;
             }
         }
+        Some(("exec", sub_matches)) => {
+            let command: Vec<String> = sub_matches.get_many::<String>("command").into_iter().flatten().cloned().collect();
+            commands::exec_command(&command, &filter(sub_matches)?, &scheduler_options(sub_matches)?).await?;
+        }
+        Some(("dev", sub_matches)) => {
+            let app_names: Vec<String> = sub_matches
+                .get_many::<String>("apps")
+                .map(|apps| apps.cloned().collect())
+                .unwrap_or_default();
+            commands::dev_apps(&app_names).await?;
+        }
+        Some(("affected", sub_matches)) => {
+            let since = sub_matches.get_one::<String>("since").map_or(DEFAULT_SINCE, |s| s.as_str());
+            commands::show_affected(since, sub_matches.get_flag("json"))?;
+        }
+        Some(("graph", sub_matches)) => {
+            commands::show_graph(&filter(sub_matches)?, sub_matches.get_flag("json"))?;
+        }
         Some(("status", _)) => {
             commands::show_status()?;
         }
+        Some(("doctor", _)) => {
+            commands::doctor()?;
+        }
         Some(("auth", _)) => {
             commands::auth_status().await?;
         }
@@ -672,13 +921,42 @@ async fn main() -> Result<()> {
             }
             _ => unreachable!(),
         },
+        Some(("cache", sub_matches)) => match sub_matches.subcommand() {
+            Some(("stats", _)) => {
+                commands::cache_stats().await?;
+            }
+            Some(("clear", _)) => {
+                commands::cache_clear().await?;
+            }
+            Some(("gc", _)) => {
+                commands::cache_gc().await?;
+            }
+            Some(("clean", cache_sub)) => {
+                commands::cache_clean(cache_sub.get_flag("tasks")).await?;
+            }
+            Some(("invalidate", cache_sub)) => {
+                let package_name = cache_sub.get_one::<String>("package")
+                    .ok_or_else(|| anyhow::anyhow!("Package name is required to invalidate cached resolutions\n💡 Usage: knot cache invalidate <package>"))?;
+                commands::cache_invalidate(package_name).await?;
+            }
+            _ => unreachable!(),
+        },
         Some(("upgrade", sub_matches)) => {
             let force = sub_matches.get_flag("force");
             commands::update_cli(force).await?;
         }
-        Some((script_name, _)) => {
-            // Try to run as a script if it's not a built-in command
-            commands::run_script(script_name).await?;
+        Some((script_name, sub_matches)) => {
+            // Try to run as a script if it's not a built-in command, forwarding any trailing arguments
+            let mut args: Vec<String> = sub_matches
+                .get_many::<std::ffi::OsString>("")
+                .into_iter()
+                .flatten()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            if args.first().is_some_and(|arg| arg == "--") {
+                args.remove(0);
+            }
+            commands::run_script(script_name, &args, false, false).await?;
         }
         None => {
             // No subcommand provided, show help
@@ -698,3 +976,86 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// `--filter`, shared by commands working on several apps and packages
+fn filter_arg() -> Arg {
+    Arg::new("filter")
+        .help("Select apps and packages: name glob, apps, packages, ./dir/**, tag:<tag>, [git-ref], ...name (with dependents), name... (with dependencies), !expr to exclude (repeatable)")
+        .long("filter")
+        .short('F')
+        .value_name("EXPR")
+        .action(clap::ArgAction::Append)
+}
+
+fn filter(matches: &ArgMatches) -> Result<filter::Filter> {
+    let expressions: Vec<String> = matches.get_many::<String>("filter").into_iter().flatten().cloned().collect();
+    filter::Filter::parse(&expressions)
+}
+
+/// Git ref `--affected` compares against when `--since` is not given
+const DEFAULT_SINCE: &str = "main";
+
+fn since_arg() -> Arg {
+    Arg::new("since")
+        .help("Git ref to compare against (default: main)")
+        .long("since")
+        .value_name("REF")
+}
+
+/// Options shared by commands that run tasks across the project
+fn scheduler_args() -> [Arg; 7] {
+    [
+        Arg::new("concurrency")
+            .help("Maximum number of tasks to run at once (default: number of CPUs)")
+            .long("concurrency")
+            .short('j')
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("output")
+            .help("How task output is shown")
+            .long("output")
+            .value_name("MODE")
+            .value_parser(["interleaved", "buffered", "tui"])
+            .default_value("interleaved"),
+        Arg::new("no-cache")
+            .help("Run every task instead of restoring cached outputs")
+            .long("no-cache")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("continue-on-error")
+            .help("Keep running the tasks that do not depend on a failed task (the default)")
+            .long("continue-on-error")
+            .conflicts_with("bail")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("bail")
+            .help("Stop every task as soon as one fails")
+            .long("bail")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("profile-out")
+            .help("Write a Chrome trace of the run (open in ui.perfetto.dev or chrome://tracing)")
+            .long("profile-out")
+            .value_name("PATH")
+            .value_parser(clap::value_parser!(std::path::PathBuf)),
+        Arg::new("report-json")
+            .help("Write a JSON summary of every task's status, duration and exit code")
+            .long("report-json")
+            .value_name("PATH")
+            .value_parser(clap::value_parser!(std::path::PathBuf)),
+    ]
+}
+
+fn scheduler_options(matches: &ArgMatches) -> Result<tasks::scheduler::SchedulerOptions> {
+    let mut options = tasks::scheduler::SchedulerOptions::default();
+    if let Some(concurrency) = matches.get_one::<usize>("concurrency") {
+        options.concurrency = *concurrency;
+    }
+    if let Some(output) = matches.get_one::<String>("output") {
+        options.output_mode = tasks::output::OutputMode::parse(output)?;
+    }
+    options.use_cache = !matches.get_flag("no-cache");
+    if matches.get_flag("bail") {
+        options.on_failure = tasks::scheduler::FailurePolicy::Bail;
+    }
+    options.profile_out = matches.get_one::<std::path::PathBuf>("profile-out").cloned();
+    options.report_json = matches.get_one::<std::path::PathBuf>("report-json").cloned();
+    Ok(options)
+}
@@ -1,7 +1,10 @@
-use crate::config::{AppConfig, KnotConfig, PackageConfig};
+use crate::config::{AppConfig, KnotConfig, PackageConfig, UserConfig};
+use crate::dependency::cache::ResolutionCache;
 use crate::dependency::{DependencyResolver, DependencySpec, PackageId, ResolutionContext, ResolutionStrategy};
 use crate::dependency::registry::{LocalPackageRegistry, RemotePackageRegistry, PackageRegistry};
 use crate::utils;
+use crate::tasks::cache::TaskCache;
+use crate::tasks::remote_cache::RemoteTaskCache;
 use crate::variables::{VariableContext, VariableInterpolation};
 use anyhow::{Context, Result};
 use console::style;
@@ -184,6 +187,39 @@ impl Project {
         dependencies
     }
 
+    /// Resolution cache under `.knot/cache`, configured from knot.yml with ~/.knot/config.yml as fallback
+    pub fn resolution_cache(&self) -> Result<ResolutionCache> {
+        let user_config = UserConfig::load()?;
+        let project_cache = self.config.cache.clone().unwrap_or_default();
+        let cache_config = project_cache.or(&user_config.cache.unwrap_or_default());
+
+        Ok(ResolutionCache::new(self.root.join(".knot").join("cache")).with_config(&cache_config))
+    }
+
+    /// Outputs and logs of cacheable script runs, under `.knot/cache/tasks`, shared through the
+    /// remote cache from knot.yml or ~/.knot/config.yml unless running offline
+    pub fn task_cache(&self) -> Result<TaskCache> {
+        let cache = TaskCache::new(self.root.join(".knot").join("cache").join("tasks"));
+        if utils::is_offline() {
+            return Ok(cache);
+        }
+
+        let remote_config = match &self.config.remote_cache {
+            Some(config) => Some(config.clone()),
+            None => UserConfig::load()?.remote_cache,
+        };
+        Ok(match remote_config {
+            Some(config) => cache.with_remote(RemoteTaskCache::new(&config)),
+            None => cache,
+        })
+    }
+
+    /// Knot Space registry client with conditional HTTP caching in ~/.knot/cache/http
+    pub fn remote_registry() -> RemotePackageRegistry {
+        RemotePackageRegistry::new("https://knot.space".to_string())
+            .with_http_cache(utils::user_cache_dir().join("http"))
+    }
+
     pub fn get_app_names(&self) -> Vec<String> {
         let mut names = Vec::new();
 
@@ -507,14 +543,14 @@ impl Project {
             local_registry.discover_packages().await
                 .context("Failed to discover local packages")?;
             
-            let remote_registry = RemotePackageRegistry::new("https://knot.space".to_string());
-            let cache_dir = self.root.join(".knot").join("cache");
+            let remote_registry = Self::remote_registry();
+            let cache = self.resolution_cache()?;
             
             self.dependency_resolver = Some(DependencyResolver::new(
                 context,
                 local_registry,
                 remote_registry,
-                cache_dir,
+                cache,
             ));
         }
         
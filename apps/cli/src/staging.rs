@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Staging directories still being written, removed when knot is interrupted
+static PARTIAL_DIRS: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// A directory written next to its final location and moved into place by [`StagingDir::commit`],
+/// so an interrupted install never leaves a half-written `knot_packages` or package directory.
+/// Dropping it without committing removes what was written so far.
+pub struct StagingDir {
+    path: PathBuf,
+    target: PathBuf,
+}
+
+impl StagingDir {
+    pub fn new(target: &Path) -> Result<Self> {
+        let name = target
+            .file_name()
+            .with_context(|| format!("Cannot stage {}: it has no directory name", target.display()))?;
+        let path = target.with_file_name(format!(".{}.partial", name.to_string_lossy()));
+
+        // Left behind by a run killed while committing, before the new contents were in place
+        let previous = previous_path(target);
+        if previous.exists() && !target.exists() {
+            fs::rename(&previous, target).with_context(|| format!("Failed to restore {}", target.display()))?;
+        }
+        // Left behind by a run that was killed before it could clean up
+        if path.exists() {
+            fs::remove_dir_all(&path).with_context(|| format!("Failed to remove stale {}", path.display()))?;
+        }
+        fs::create_dir_all(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        if let Ok(mut dirs) = PARTIAL_DIRS.lock() {
+            dirs.insert(path.clone());
+        }
+
+        Ok(Self {
+            path,
+            target: target.to_path_buf(),
+        })
+    }
+
+    /// Where to write the contents
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replace the target with the staged contents. The previous contents are moved aside
+    /// first and only deleted once the new ones are in place, or put back if that fails.
+    pub fn commit(self) -> Result<()> {
+        let previous = previous_path(&self.target);
+        if previous.exists() {
+            fs::remove_dir_all(&previous).with_context(|| format!("Failed to remove stale {}", previous.display()))?;
+        }
+
+        let replacing = self.target.exists();
+        if replacing {
+            fs::rename(&self.target, &previous)
+                .with_context(|| format!("Failed to move existing {} aside", self.target.display()))?;
+        }
+        if let Err(e) = fs::rename(&self.path, &self.target) {
+            if replacing {
+                let _ = fs::rename(&previous, &self.target);
+            }
+            return Err(e).with_context(|| format!("Failed to move {} into place", self.target.display()));
+        }
+        if replacing {
+            // The new contents are in place; a leftover copy is removed by the next commit
+            let _ = fs::remove_dir_all(&previous);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if let Ok(mut dirs) = PARTIAL_DIRS.lock() {
+            dirs.remove(&self.path);
+        }
+        if self.path.exists() {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// Where [`StagingDir::commit`] keeps the previous contents of `target` while replacing them
+fn previous_path(target: &Path) -> PathBuf {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    target.with_file_name(format!(".{}.old", name))
+}
+
+/// Remove every staging directory still being written; the targets keep their previous contents
+pub fn roll_back() {
+    let Ok(dirs) = PARTIAL_DIRS.lock() else {
+        return;
+    };
+    for dir in dirs.iter() {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_staging_dir_commits_or_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("knot_packages");
+        fs::create_dir_all(target.join("old")).unwrap();
+
+        // Dropped before committing: the previous contents stay
+        let staging = StagingDir::new(&target).unwrap();
+        fs::write(staging.path().join("half-written"), "").unwrap();
+        drop(staging);
+        assert!(target.join("old").exists());
+        assert!(!temp_dir.path().join(".knot_packages.partial").exists());
+
+        let staging = StagingDir::new(&target).unwrap();
+        fs::create_dir_all(staging.path().join("new")).unwrap();
+        staging.commit().unwrap();
+        assert!(target.join("new").exists());
+        assert!(!target.join("old").exists());
+        assert!(!temp_dir.path().join(".knot_packages.partial").exists());
+        assert!(!temp_dir.path().join(".knot_packages.old").exists());
+
+        // Moving the staged contents into place fails: the previous contents are put back
+        let staging = StagingDir::new(&target).unwrap();
+        fs::remove_dir_all(staging.path()).unwrap();
+        assert!(staging.commit().is_err());
+        assert!(target.join("new").exists());
+    }
+}
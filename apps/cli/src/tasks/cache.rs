@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use glob::{MatchOptions, Pattern};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::remote_cache::RemoteTaskCache;
+use super::Task;
+use crate::env;
+use crate::ignore::KnotIgnore;
+
+/// Archive entry holding the task's captured output
+const LOG_ENTRY: &str = ".knot-task.log";
+
+/// Bumped whenever the hash inputs change so old entries stop matching
+const HASH_VERSION: &str = "1";
+
+/// Outputs and logs of successful task runs, keyed by a hash of everything the run depended on.
+/// Each entry is a `<hash>.tar.gz` holding the declared outputs plus the captured log, kept
+/// locally and optionally shared through a remote cache server.
+#[derive(Debug, Clone)]
+pub struct TaskCache {
+    dir: PathBuf,
+    remote: Option<RemoteTaskCache>,
+}
+
+/// A cached run restored into the task's directory
+#[derive(Debug)]
+pub struct CacheHit {
+    pub log: String,
+    /// Downloaded from the remote cache rather than found locally
+    pub remote: bool,
+}
+
+impl TaskCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, remote: None }
+    }
+
+    pub fn with_remote(mut self, remote: RemoteTaskCache) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, hash: &str) -> PathBuf {
+        self.dir.join(format!("{}.tar.gz", hash))
+    }
+
+    /// Hash the task's command (after variable interpolation), shell, environment (the
+    /// variables knot adds and those let through by `env_passthrough`, except machine- and
+    /// time-specific `KNOT_*` variables), declared outputs, matching input files
+    /// and the hashes of the tasks it depends on
+    pub fn hash(task: &Task, dependency_hashes: &[String]) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(HASH_VERSION.as_bytes());
+        let shell = task.shell.as_deref().unwrap_or_default();
+        for part in [task.node.kind(), task.node.name(), task.script.as_str(), task.command.as_str(), shell] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for (key, value) in task.env.iter().filter(|(key, _)| !env::is_volatile(key)) {
+            hasher.update(format!("{}={}", key, value).as_bytes());
+            hasher.update([0]);
+        }
+        for output in &task.outputs {
+            hasher.update(output.as_bytes());
+            hasher.update([0]);
+        }
+        for dependency_hash in dependency_hashes {
+            hasher.update(dependency_hash.as_bytes());
+        }
+
+        for relative_path in input_files(task)? {
+            let content = fs::read(task.working_dir.join(&relative_path))
+                .with_context(|| format!("Failed to read task input '{}'", relative_path))?;
+            hasher.update(relative_path.as_bytes());
+            hasher.update([0]);
+            hasher.update(Sha256::digest(&content));
+        }
+
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Restore the outputs of a cached run into the task's directory, looking in the local
+    /// cache first and then the remote one. Returns `None` if nothing is cached under `hash`.
+    pub async fn restore(&self, hash: &str, task: &Task) -> Result<Option<CacheHit>> {
+        let entry_path = self.entry_path(hash);
+        let (archive, remote) = if entry_path.exists() {
+            let archive = fs::read(&entry_path)
+                .with_context(|| format!("Failed to read cached task output: {}", entry_path.display()))?;
+            (archive, false)
+        } else {
+            let Some(remote) = &self.remote else {
+                return Ok(None);
+            };
+            let Some(archive) = remote.get(hash).await? else {
+                return Ok(None);
+            };
+            self.write_entry(hash, &archive)?;
+            (archive, true)
+        };
+
+        let log = unpack(&archive, task)?;
+        Ok(Some(CacheHit { log, remote }))
+    }
+
+    /// Archive the task's outputs and log under `hash`, uploading them unless the remote is read-only
+    pub async fn store(&self, hash: &str, task: &Task, log: &str) -> Result<()> {
+        let archive = pack(task, log)?;
+        self.write_entry(hash, &archive)?;
+
+        if let Some(remote) = self.remote.as_ref().filter(|remote| !remote.is_read_only()) {
+            remote.put(hash, archive).await?;
+        }
+        Ok(())
+    }
+
+    fn write_entry(&self, hash: &str, archive: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create task cache directory: {}", self.dir.display()))?;
+        let entry_path = self.entry_path(hash);
+        let partial_path = entry_path.with_extension("partial");
+        fs::write(&partial_path, archive)?;
+        fs::rename(&partial_path, &entry_path)?;
+        Ok(())
+    }
+
+    /// Remove every cached task run, returning how many there were
+    pub fn clean(&self) -> Result<usize> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(0);
+        };
+        let count = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tar.gz"))
+            .count();
+
+        fs::remove_dir_all(&self.dir)
+            .with_context(|| format!("Failed to remove task cache: {}", self.dir.display()))?;
+        Ok(count)
+    }
+}
+
+/// Tarball of the task's outputs plus its log
+fn pack(task: &Task, log: &str) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+    for output in &task.outputs {
+        let path = task.working_dir.join(output);
+        if path.is_dir() {
+            builder.append_dir_all(output, &path)?;
+        } else if path.is_file() {
+            builder.append_path_with_name(&path, output)?;
+        }
+    }
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(log.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, LOG_ENTRY, log.as_bytes())?;
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// Replace the task's outputs with the archived ones and return the archived log
+fn unpack(archive: &[u8], task: &Task) -> Result<String> {
+    for output in &task.outputs {
+        let path = task.working_dir.join(output);
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else if path.exists() {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    let mut log = String::new();
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_os_str() == LOG_ENTRY {
+            entry.read_to_string(&mut log)?;
+        } else {
+            entry.unpack_in(&task.working_dir)?;
+        }
+    }
+    Ok(log)
+}
+
+/// Files matching the task's input globs (every file when none are declared), sorted and
+/// relative to its directory. Outputs, `.knotignore`d paths and `.knot` are never inputs.
+fn input_files(task: &Task) -> Result<Vec<String>> {
+    let patterns = task
+        .inputs
+        .iter()
+        .map(|input| {
+            Pattern::new(input).with_context(|| {
+                format!("Invalid input glob '{}' in {} '{}'", input, task.node.kind(), task.label())
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    let ignore = KnotIgnore::from_file(&task.working_dir.join(".knotignore"))
+        .unwrap_or_else(|_| KnotIgnore::default());
+
+    let relative = |path: &Path| {
+        path.strip_prefix(&task.working_dir)
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default()
+    };
+    let excluded = |relative_path: &str| {
+        relative_path == ".knot"
+            || ignore.is_ignored(relative_path)
+            || task.outputs.iter().any(|output| {
+                let output = output.trim_end_matches('/');
+                relative_path == output || relative_path.starts_with(&format!("{}/", output))
+            })
+    };
+
+    let mut files: Vec<String> = WalkDir::new(&task.working_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !excluded(&relative(entry.path())))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| relative(entry.path()))
+        .filter(|relative_path| {
+            patterns.is_empty() || patterns.iter().any(|pattern| pattern.matches_with(relative_path, options))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ProjectNode;
+    use tempfile::TempDir;
+
+    fn task(dir: &Path) -> Task {
+        Task {
+            inputs: vec!["src/**/*.ts".to_string()],
+            outputs: vec!["dist".to_string()],
+            ..Task::new(ProjectNode::Package("utils".to_string()), "build", "tsc", dir.to_path_buf())
+        }
+    }
+
+    #[test]
+    fn test_hash_tracks_inputs_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("src/lib")).unwrap();
+        fs::write(dir.join("src/lib/index.ts"), "export const a = 1;").unwrap();
+        let task = task(dir);
+
+        let before = TaskCache::hash(&task, &[]).unwrap();
+        fs::write(dir.join("README.md"), "not an input").unwrap();
+        fs::create_dir_all(dir.join("dist")).unwrap();
+        fs::write(dir.join("dist/index.js"), "exports.a = 1;").unwrap();
+        assert_eq!(before, TaskCache::hash(&task, &[]).unwrap());
+
+        assert_ne!(before, TaskCache::hash(&task, &["upstream".to_string()]).unwrap());
+        fs::write(dir.join("src/lib/index.ts"), "export const a = 2;").unwrap();
+        assert_ne!(before, TaskCache::hash(&task, &[]).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_store_and_restore_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let cache = TaskCache::new(dir.join(".knot/cache/tasks"));
+        let task = task(dir);
+
+        assert!(cache.restore("abc", &task).await.unwrap().is_none());
+
+        fs::create_dir_all(dir.join("dist/nested")).unwrap();
+        fs::write(dir.join("dist/nested/index.js"), "built").unwrap();
+        cache.store("abc", &task, "compiled 1 file\n").await.unwrap();
+
+        fs::remove_dir_all(dir.join("dist")).unwrap();
+        fs::create_dir_all(dir.join("dist")).unwrap();
+        fs::write(dir.join("dist/stale.js"), "stale").unwrap();
+
+        let hit = cache.restore("abc", &task).await.unwrap().unwrap();
+        assert_eq!(hit.log, "compiled 1 file\n");
+        assert!(!hit.remote);
+        assert_eq!(fs::read_to_string(dir.join("dist/nested/index.js")).unwrap(), "built");
+        assert!(!dir.join("dist/stale.js").exists());
+
+        assert_eq!(cache.clean().unwrap(), 1);
+        assert!(cache.restore("abc", &task).await.unwrap().is_none());
+    }
+}
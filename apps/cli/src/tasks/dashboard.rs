@@ -0,0 +1,323 @@
+use console::{style, Color};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Print, ResetColor};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use super::summary::{format_duration, status_label};
+use super::{TaskResult, TaskStatus};
+
+/// Whether a dashboard has the terminal in raw mode and on the alternate screen
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// How often the dashboard is redrawn while waiting for keys
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Request from the dashboard to the scheduler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardCommand {
+    /// Stop the task if it is running, then run it again
+    Restart(usize),
+    /// Stop the task
+    Kill(usize),
+    /// Stop every task and close the dashboard
+    Quit,
+}
+
+#[derive(Debug, Clone)]
+enum TaskState {
+    Waiting,
+    Running(Instant),
+    Finished { status: &'static str, color: Color, duration: Duration },
+}
+
+struct TaskView {
+    label: String,
+    state: TaskState,
+    /// Output of the task, written by its [`super::output::TaskOutput`]
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+/// Full-screen view of running tasks: a task list with live status and timing above a
+/// scrollable log of the selected task. Keys are turned into [`DashboardCommand`]s.
+pub struct Dashboard {
+    tasks: Arc<Mutex<Vec<TaskView>>>,
+    commands: mpsc::UnboundedReceiver<DashboardCommand>,
+    stop: Arc<AtomicBool>,
+    ui: Option<JoinHandle<()>>,
+}
+
+impl Dashboard {
+    /// Switch the terminal to the dashboard, showing `labels` as waiting tasks
+    pub fn start(labels: &[&str]) -> io::Result<Self> {
+        let tasks: Vec<TaskView> = labels
+            .iter()
+            .map(|label| TaskView {
+                label: label.to_string(),
+                state: TaskState::Waiting,
+                lines: Default::default(),
+            })
+            .collect();
+        let tasks = Arc::new(Mutex::new(tasks));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, commands) = mpsc::unbounded_channel();
+
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        ACTIVE.store(true, Ordering::SeqCst);
+
+        let ui = {
+            let (tasks, stop) = (tasks.clone(), stop.clone());
+            std::thread::spawn(move || {
+                let _ = run_ui(&tasks, &sender, &stop);
+            })
+        };
+
+        Ok(Self {
+            tasks,
+            commands,
+            stop,
+            ui: Some(ui),
+        })
+    }
+
+    /// Where the output of task `index` goes
+    pub fn lines(&self, index: usize) -> Arc<Mutex<Vec<String>>> {
+        self.tasks.lock().unwrap()[index].lines.clone()
+    }
+
+    /// Show a task as running; a rerun is separated from the previous output
+    pub fn started(&self, index: usize) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let task = &mut tasks[index];
+        let mut lines = task.lines.lock().unwrap();
+        if !lines.is_empty() {
+            lines.push(style("── restarted ──").dim().to_string());
+        }
+        drop(lines);
+        task.state = TaskState::Running(Instant::now());
+    }
+
+    pub fn finished(&self, index: usize, result: &TaskResult) {
+        let color = match result.status {
+            TaskStatus::Success => Color::Green,
+            TaskStatus::Failed(_) | TaskStatus::TimedOut => Color::Red,
+            TaskStatus::Skipped | TaskStatus::Cancelled => Color::Yellow,
+        };
+        self.tasks.lock().unwrap()[index].state = TaskState::Finished {
+            status: status_label(result),
+            color,
+            duration: result.duration,
+        };
+    }
+
+    /// Show a task as waiting again before it is rerun
+    pub fn waiting(&self, index: usize) {
+        self.tasks.lock().unwrap()[index].state = TaskState::Waiting;
+    }
+
+    /// Next request from the keyboard; `None` once the dashboard is closed
+    pub async fn next_command(&mut self) -> Option<DashboardCommand> {
+        self.commands.recv().await
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(ui) = self.ui.take() {
+            let _ = ui.join();
+        }
+        restore_terminal();
+    }
+}
+
+/// Give the terminal back when knot exits while a dashboard is open, e.g. on SIGTERM
+pub fn restore_terminal() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Selection and scroll position, owned by the UI thread
+#[derive(Default)]
+struct View {
+    selected: usize,
+    /// Log lines scrolled up from the bottom; 0 follows new output
+    scroll: usize,
+    quitting: bool,
+}
+
+fn run_ui(
+    tasks: &Mutex<Vec<TaskView>>,
+    commands: &mpsc::UnboundedSender<DashboardCommand>,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let mut view = View::default();
+    let mut stdout = io::stdout();
+
+    while !stop.load(Ordering::Relaxed) {
+        draw(&mut stdout, tasks, &view)?;
+        if !event::poll(REFRESH_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press || view.quitting {
+            continue;
+        }
+
+        let rows = terminal::size().map_or(20, |(_, rows)| rows as usize);
+        let page = (rows / 2).max(1);
+        let (count, max_scroll) = {
+            let tasks = tasks.lock().unwrap();
+            let total = tasks.get(view.selected).map_or(0, |task| task.lines.lock().unwrap().len());
+            (tasks.len(), max_scroll(total, log_height(rows, tasks.len())))
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => view.quitting = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => view.quitting = true,
+            KeyCode::Up | KeyCode::Char('k') => {
+                view.selected = view.selected.saturating_sub(1);
+                view.scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                view.selected = (view.selected + 1).min(count.saturating_sub(1));
+                view.scroll = 0;
+            }
+            KeyCode::PageUp => view.scroll = (view.scroll + page).min(max_scroll),
+            KeyCode::PageDown => view.scroll = view.scroll.saturating_sub(page),
+            KeyCode::Home | KeyCode::Char('g') => view.scroll = max_scroll,
+            KeyCode::End | KeyCode::Char('G') => view.scroll = 0,
+            KeyCode::Char('r') => {
+                let _ = commands.send(DashboardCommand::Restart(view.selected));
+            }
+            KeyCode::Char('x') => {
+                let _ = commands.send(DashboardCommand::Kill(view.selected));
+            }
+            _ => {}
+        }
+        if view.quitting {
+            let _ = commands.send(DashboardCommand::Quit);
+        }
+    }
+    Ok(())
+}
+
+fn draw(stdout: &mut io::Stdout, tasks: &Mutex<Vec<TaskView>>, view: &View) -> io::Result<()> {
+    let (columns, rows) = terminal::size()?;
+    let (width, rows) = (columns as usize, rows as usize);
+    let tasks = tasks.lock().unwrap();
+    let mut screen: Vec<String> = Vec::with_capacity(rows);
+
+    let done = tasks.iter().filter(|task| matches!(task.state, TaskState::Finished { .. })).count();
+    let running = tasks.iter().filter(|task| matches!(task.state, TaskState::Running(_))).count();
+    screen.push(
+        style(format!(" knot · {}/{} done · {} running", done, tasks.len(), running))
+            .bold()
+            .to_string(),
+    );
+
+    let list_height = list_height(rows, tasks.len());
+    let first = (view.selected + 1).saturating_sub(list_height);
+    let label_width = tasks.iter().map(|task| task.label.len()).max().unwrap_or(0);
+    for (index, task) in tasks.iter().enumerate().skip(first).take(list_height) {
+        let (status, time) = match &task.state {
+            TaskState::Waiting => (style(format!("{:8}", "waiting")).dim(), String::new()),
+            TaskState::Running(since) => (style(format!("{:8}", "running")).cyan(), format_duration(since.elapsed())),
+            TaskState::Finished { status, color, duration } => {
+                (style(format!("{:8}", status)).fg(*color), format_duration(*duration))
+            }
+        };
+        let marker = if index == view.selected { style("›").bold().to_string() } else { " ".to_string() };
+        let row = format!("{} {:label_width$}  {}  {:>8}", marker, task.label, status, time, label_width = label_width);
+        screen.push(if index == view.selected { style(row).reverse().to_string() } else { row });
+    }
+
+    let footer = if view.quitting {
+        " Stopping tasks...".to_string()
+    } else if done == tasks.len() {
+        " All tasks finished · ↑↓ select · r restart · PgUp/PgDn scroll · q quit".to_string()
+    } else {
+        " ↑↓ select · r restart · x kill · PgUp/PgDn scroll · q quit".to_string()
+    };
+    let log_height = log_height(rows, tasks.len());
+
+    if let Some(task) = tasks.get(view.selected) {
+        let lines = task.lines.lock().unwrap();
+        let (start, end) = log_window(lines.len(), log_height, view.scroll);
+        let position = if end < lines.len() { format!(" (line {} of {})", end, lines.len()) } else { String::new() };
+        screen.push(style(format!("── {}{} ", task.label, position)).dim().to_string());
+        screen.extend(lines[start..end].iter().map(|line| printable(line)));
+    }
+    screen.resize(rows.saturating_sub(1), String::new());
+    screen.push(style(footer).dim().to_string());
+
+    for (row, line) in screen.iter().enumerate() {
+        queue!(
+            stdout,
+            cursor::MoveTo(0, row as u16),
+            Print(console::truncate_str(line, width, "")),
+            ResetColor,
+            terminal::Clear(ClearType::UntilNewLine)
+        )?;
+    }
+    stdout.flush()
+}
+
+/// Rows of the task list on a screen of `rows` rows
+fn list_height(rows: usize, task_count: usize) -> usize {
+    task_count.min((rows / 3).max(3))
+}
+
+/// Rows left for the log below the header, the task list, the log title and the footer
+fn log_height(rows: usize, task_count: usize) -> usize {
+    rows.saturating_sub(list_height(rows, task_count) + 3)
+}
+
+/// Furthest a log with `total` lines shown in `height` rows can be scrolled up
+fn max_scroll(total: usize, height: usize) -> usize {
+    total.saturating_sub(height)
+}
+
+/// Range of a log with `total` lines shown in `height` rows, `scroll` lines up from the end
+fn log_window(total: usize, height: usize, scroll: usize) -> (usize, usize) {
+    let end = total - scroll.min(max_scroll(total, height));
+    (end.saturating_sub(height), end)
+}
+
+/// Keep what a terminal would show of a line: the text after the last carriage return
+/// (progress bars), with tabs expanded
+fn printable(line: &str) -> String {
+    line.rsplit('\r').next().unwrap_or_default().replace('\t', "    ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_window() {
+        // Follows the end of the log
+        assert_eq!(log_window(100, 10, 0), (90, 100));
+        assert_eq!(log_window(5, 10, 0), (0, 5));
+        // Scrolling stops at the first line
+        assert_eq!(log_window(100, 10, 30), (60, 70));
+        assert_eq!(log_window(100, 10, usize::MAX), (0, 10));
+        // Home then PgDn scrolls down from the first line right away
+        let home = max_scroll(100, 10);
+        assert_eq!(log_window(100, 10, home), (0, 10));
+        assert_eq!(log_window(100, 10, home.saturating_sub(5)), (5, 15));
+        assert_eq!(max_scroll(5, 10), 0);
+        assert_eq!(log_height(24, 4), 17);
+        assert_eq!(printable("50%\r100%\tdone"), "100%    done");
+    }
+}
@@ -0,0 +1,640 @@
+pub mod cache;
+pub mod dashboard;
+pub mod output;
+pub mod process;
+pub mod remote_cache;
+pub mod report;
+pub mod scheduler;
+pub mod summary;
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+use crate::config::Script;
+use crate::env;
+use crate::graph::{ProjectGraph, ProjectNode};
+use crate::project::Project;
+use crate::utils;
+use output::TaskOutput;
+
+/// A script of one app or package, identified by the node and the script name
+pub type TaskId = (ProjectNode, String);
+
+/// A script to run in one app or package
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub node: ProjectNode,
+    /// Name of the script in the node's config
+    pub script: String,
+    /// Name shown in prefixed output and summaries
+    pub label: String,
+    pub command: String,
+    pub working_dir: PathBuf,
+    pub env: BTreeMap<String, String>,
+    /// Run with `env` alone instead of on top of knot's environment (`env_passthrough`)
+    pub hermetic: bool,
+    /// Shell to run the command with instead of the platform default
+    pub shell: Option<String>,
+    pub timeout: Option<Duration>,
+    /// Extra attempts after the command fails or times out
+    pub retries: u32,
+    /// Input globs, hashed together with the command to key the task cache
+    pub inputs: Vec<String>,
+    /// Paths restored from the task cache instead of running the command
+    pub outputs: Vec<String>,
+    /// Tasks that must succeed first, from the script's `depends_on`
+    pub depends_on: Vec<TaskId>,
+}
+
+impl Task {
+    pub fn new(node: ProjectNode, script: &str, command: &str, working_dir: PathBuf) -> Self {
+        Self {
+            label: node.name().to_string(),
+            node,
+            script: script.to_string(),
+            command: command.to_string(),
+            working_dir,
+            env: BTreeMap::new(),
+            hermetic: false,
+            shell: None,
+            timeout: None,
+            retries: 0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            depends_on: Vec::new(),
+        }
+    }
+
+    /// Task for the `name` script of `node` with extra command-line `args`; `depends_on` is
+    /// resolved by the caller. The environment layers `.env` files and `KNOT_*` variables
+    /// under the script's own `env`, and replaces knot's with `env_passthrough`.
+    pub fn from_script(node: ProjectNode, name: &str, script: &Script, args: &[String], project: &Project) -> Result<Self> {
+        let dir = node.dir(&project.root);
+        let env = env::script_env(Some(project), Some(&node), &dir, script)?;
+        let working_dir = match script.cwd() {
+            Some(cwd) => dir.join(cwd),
+            None => dir,
+        };
+
+        Ok(Self {
+            env,
+            hermetic: script.env_passthrough().is_some(),
+            shell: script.shell().map(|shell| shell.to_string()),
+            timeout: script.timeout(),
+            retries: script.retries(),
+            inputs: script.inputs().to_vec(),
+            outputs: script.outputs().to_vec(),
+            ..Self::new(node, name, &with_args(script.command(), args, script.shell()), working_dir)
+        })
+    }
+
+    pub fn id(&self) -> TaskId {
+        (self.node.clone(), self.script.clone())
+    }
+
+    /// Only scripts declaring their inputs or outputs are cached
+    pub fn is_cacheable(&self) -> bool {
+        !self.inputs.is_empty() || !self.outputs.is_empty()
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskStatus {
+    Success,
+    Failed(i32),
+    /// Stopped after running longer than the script's timeout
+    TimedOut,
+    /// Not run because a task it depends on failed
+    Skipped,
+    /// Stopped by `--bail` after another task failed, or from the dashboard
+    Cancelled,
+}
+
+impl TaskStatus {
+    /// Exit status of knot for a run ending with this status; 124 for timeouts, like `timeout(1)`
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TaskStatus::Success => 0,
+            TaskStatus::Failed(code) => (*code).max(1),
+            TaskStatus::TimedOut => 124,
+            TaskStatus::Skipped | TaskStatus::Cancelled => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskResult {
+    pub task: Task,
+    pub status: TaskStatus,
+    pub duration: Duration,
+    /// Task cache key, when the task was eligible for caching
+    pub hash: Option<String>,
+    /// Outputs were restored from the cache instead of running the command
+    pub cached: bool,
+    /// Times the command was started, including retries
+    pub attempts: u32,
+    /// When the scheduler started the task, `None` when it never ran
+    pub started: Option<Instant>,
+    /// Concurrency slot the task ran in, for timelines
+    pub lane: Option<usize>,
+}
+
+impl TaskResult {
+    pub fn is_success(&self) -> bool {
+        self.status == TaskStatus::Success
+    }
+}
+
+/// Exit status for a batch of tasks: that of the worst failed task, 0 when all succeeded
+pub fn worst_exit_code(results: &[TaskResult]) -> i32 {
+    let failed = results
+        .iter()
+        .filter(|result| matches!(result.status, TaskStatus::Failed(_) | TaskStatus::TimedOut))
+        .map(|result| result.status.exit_code())
+        .max();
+    match failed {
+        Some(code) => code,
+        None if results.iter().all(TaskResult::is_success) => 0,
+        None => 1,
+    }
+}
+
+/// Error making knot exit with `code` instead of 1, e.g. the exit code of a failed script
+#[derive(Debug)]
+pub struct ExitError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl std::fmt::Display for ExitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ExitError {}
+
+/// The `name` script of an app or package
+pub fn node_script<'a>(project: &'a Project, node: &ProjectNode, name: &str) -> Option<&'a Script> {
+    let scripts = match node {
+        ProjectNode::App(app) => project.apps.get(app)?.scripts.as_ref(),
+        ProjectNode::Package(package) => project.packages.get(package)?.scripts.as_ref(),
+    };
+    scripts?.get(name)
+}
+
+/// `script` in each of `nodes` defining it, plus the scripts they depend on, including those of
+/// apps and packages outside `nodes`. `args` are passed to `script` only, not to its dependencies.
+pub fn collect_script_tasks(
+    project: &Project,
+    graph: &ProjectGraph,
+    nodes: &BTreeSet<ProjectNode>,
+    script: &str,
+    args: &[String],
+) -> Result<Vec<Task>> {
+    let roots = nodes
+        .iter()
+        .filter(|node| node_script(project, node, script).is_some())
+        .map(|node| (node.clone(), script.to_string()))
+        .collect();
+    expand_tasks(project, graph, roots, script, args, |node, name| node_script(project, node, name).cloned())
+}
+
+/// The `script` of every local package `node` depends on, for a `^script` dependency
+pub fn collect_upstream_tasks(
+    project: &Project,
+    graph: &ProjectGraph,
+    node: &ProjectNode,
+    script: &str,
+) -> Result<Vec<Task>> {
+    let roots = graph
+        .with_dependencies(std::slice::from_ref(node))
+        .into_iter()
+        .filter(|upstream| upstream != node && node_script(project, upstream, script).is_some())
+        .map(|upstream| (upstream, script.to_string()))
+        .collect();
+    expand_tasks(project, graph, roots, script, &[], |node, name| node_script(project, node, name).cloned())
+}
+
+/// Name of the hook run before `script`, e.g. `prebuild`
+pub fn pre_hook(script: &str) -> String {
+    format!("pre{}", script)
+}
+
+/// Name of the hook run after `script`, e.g. `postbuild`
+pub fn post_hook(script: &str) -> String {
+    format!("post{}", script)
+}
+
+/// Tasks for `roots` and everything they transitively `depends_on`, plus their `pre`/`post`
+/// hooks unless scripts are ignored, packages before the apps that use them. `script_of` looks
+/// up a script of a node. Scripts other than `requested` are labelled `node:script` and get no `args`.
+pub fn expand_tasks(
+    project: &Project,
+    graph: &ProjectGraph,
+    roots: Vec<TaskId>,
+    requested: &str,
+    args: &[String],
+    script_of: impl Fn(&ProjectNode, &str) -> Option<Script>,
+) -> Result<Vec<Task>> {
+    let run_hooks = !utils::ignore_scripts();
+    let mut tasks: BTreeMap<TaskId, Task> = BTreeMap::new();
+    // Hook tasks, mapped to the task they run after (for post hooks)
+    let mut hooks: BTreeMap<TaskId, Option<TaskId>> = BTreeMap::new();
+    let mut stack = roots;
+
+    while let Some((node, name)) = stack.pop() {
+        let id = (node.clone(), name.clone());
+        if tasks.contains_key(&id) {
+            continue;
+        }
+        let Some(script) = script_of(&node, &name) else {
+            continue;
+        };
+
+        let task_args = if name == requested { args } else { &[] };
+        let mut task = Task::from_script(node.clone(), &name, &script, task_args, project)?;
+        if name != requested {
+            task.label = format!("{}:{}", node.name(), name);
+        }
+
+        for dependency in script.depends_on() {
+            match dependency.strip_prefix('^') {
+                Some(upstream) => {
+                    task.depends_on.extend(
+                        graph
+                            .with_dependencies(std::slice::from_ref(&node))
+                            .into_iter()
+                            .filter(|dep| *dep != node && script_of(dep, upstream).is_some())
+                            .map(|dep| (dep, upstream.to_string())),
+                    );
+                }
+                None => {
+                    if script_of(&node, dependency).is_none() {
+                        anyhow::bail!(
+                            "Script '{}' of {} '{}' depends on '{}', which is not defined there\n💡 Add a '{}' script or remove it from depends_on",
+                            name, node.kind(), node.name(), dependency, dependency
+                        );
+                    }
+                    task.depends_on.push((node.clone(), dependency.clone()));
+                }
+            }
+        }
+
+        match hooks.get(&id) {
+            Some(Some(main)) => task.depends_on.push(main.clone()),
+            Some(None) => {}
+            None if run_hooks => {
+                let pre = (node.clone(), pre_hook(&name));
+                if script_of(&node, &pre.1).is_some() {
+                    hooks.insert(pre.clone(), None);
+                    task.depends_on.push(pre);
+                }
+                let post = (node.clone(), post_hook(&name));
+                if script_of(&node, &post.1).is_some() {
+                    hooks.insert(post.clone(), Some(id.clone()));
+                    stack.push(post);
+                }
+            }
+            None => {}
+        }
+
+        stack.extend(task.depends_on.iter().cloned());
+        tasks.insert(id, task);
+    }
+
+    check_cycles(&tasks)?;
+
+    let order = graph.topological_order(&graph.all_nodes())?;
+    let position: HashMap<&ProjectNode, usize> = order.iter().enumerate().map(|(i, node)| (node, i)).collect();
+    let mut tasks: Vec<Task> = tasks.into_values().collect();
+    tasks.sort_by_key(|task| position.get(&task.node).copied().unwrap_or(usize::MAX));
+    Ok(tasks)
+}
+
+/// Fail on scripts that (indirectly) depend on themselves
+fn check_cycles(tasks: &BTreeMap<TaskId, Task>) -> Result<()> {
+    let mut remaining: BTreeMap<&TaskId, Vec<&TaskId>> = tasks
+        .iter()
+        .map(|(id, task)| (id, task.depends_on.iter().filter(|dep| tasks.contains_key(*dep)).collect()))
+        .collect();
+
+    while !remaining.is_empty() {
+        let ready: Vec<&TaskId> = remaining
+            .iter()
+            .filter(|(_, deps)| deps.is_empty())
+            .map(|(id, _)| *id)
+            .collect();
+        if ready.is_empty() {
+            let cycle: Vec<&str> = remaining.keys().map(|id| tasks[*id].label()).collect();
+            anyhow::bail!(
+                "Circular depends_on between scripts: {}\n💡 Remove one of the depends_on entries",
+                cycle.join(", ")
+            );
+        }
+        for id in ready {
+            remaining.remove(id);
+            for deps in remaining.values_mut() {
+                deps.retain(|dep| *dep != id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Placeholder in a script command replaced by the arguments given after `--`
+pub const ARGS_PLACEHOLDER: &str = "{{args}}";
+
+/// `command` with `args` shell-escaped in place of `{{args}}`, or appended when the command
+/// does not reference them
+pub fn with_args(command: &str, args: &[String], shell: Option<&str>) -> String {
+    let escaped: Vec<String> = args.iter().map(|arg| shell_escape(arg, shell)).collect();
+    let escaped = escaped.join(" ");
+
+    if command.contains(ARGS_PLACEHOLDER) {
+        command.replace(ARGS_PLACEHOLDER, &escaped)
+    } else if escaped.is_empty() {
+        command.to_string()
+    } else {
+        format!("{} {}", command, escaped)
+    }
+}
+
+/// Quote `arg` so `shell` passes it to the command as a single, literal argument
+pub fn shell_escape(arg: &str, shell: Option<&str>) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@+%".contains(c));
+    if is_plain {
+        return arg.to_string();
+    }
+
+    match shell_name(shell).as_str() {
+        "cmd" => format!("\"{}\"", arg.replace('"', "\"\"")),
+        "powershell" | "pwsh" => format!("'{}'", arg.replace('\'', "''")),
+        _ => format!("'{}'", arg.replace('\'', "'\\''")),
+    }
+}
+
+fn default_shell() -> &'static str {
+    if cfg!(target_os = "windows") { "cmd" } else { "sh" }
+}
+
+/// Lowercase file stem of `shell`, e.g. `bash` for `/bin/bash`
+fn shell_name(shell: Option<&str>) -> String {
+    Path::new(shell.unwrap_or(default_shell()))
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Command running `script` through `shell`, or the platform's default shell
+pub fn shell_command(shell: Option<&str>, script: &str) -> tokio::process::Command {
+    let flag = match shell_name(shell).as_str() {
+        "cmd" => "/C",
+        "powershell" | "pwsh" => "-Command",
+        _ => "-c",
+    };
+
+    let mut command = tokio::process::Command::new(shell.unwrap_or(default_shell()));
+    command.arg(flag).arg(script);
+    command
+}
+
+/// Run the task through the shell, streaming its output with the task's prefix, retrying up
+/// to `task.retries` times after it fails or times out. Stops the command once `cancel` turns true.
+pub async fn run_task(task: &Task, output: &TaskOutput, cancel: watch::Receiver<bool>) -> Result<TaskResult> {
+    let start = Instant::now();
+    let mut attempt = 1;
+    loop {
+        let status = run_attempt(task, output, cancel.clone()).await?;
+        let failed = matches!(status, TaskStatus::Failed(_) | TaskStatus::TimedOut);
+        if !failed || attempt > task.retries || *cancel.borrow() {
+            return Ok(TaskResult {
+                task: task.clone(),
+                status,
+                duration: start.elapsed(),
+                hash: None,
+                cached: false,
+                attempts: attempt,
+                started: Some(start),
+                lane: None,
+            });
+        }
+
+        attempt += 1;
+        output.line(&format!("🔁 Retrying, attempt {} of {}", attempt, task.retries + 1));
+    }
+}
+
+/// How a single run of a task's command ended
+enum AttemptEnd {
+    Exited(std::io::Result<std::process::ExitStatus>),
+    TimedOut,
+    Cancelled,
+}
+
+async fn run_attempt(task: &Task, output: &TaskOutput, mut cancel: watch::Receiver<bool>) -> Result<TaskStatus> {
+    process::halt_if_stopping().await;
+    let mut command = shell_command(task.shell.as_deref(), &task.command);
+    if task.hermetic {
+        command.env_clear();
+    }
+    command
+        .envs(&task.env)
+        .current_dir(&task.working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    process::isolate(&mut command);
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start '{}' in {} '{}'", task.command, task.node.kind(), task.label()))?;
+    let pid = child.id();
+    if let Some(pid) = pid {
+        process::register(pid);
+    }
+
+    let stdout = output.forward(child.stdout.take());
+    let stderr = output.forward(child.stderr.take());
+    let exited = async {
+        match task.timeout {
+            Some(limit) => match tokio::time::timeout(limit, child.wait()).await {
+                Ok(status) => AttemptEnd::Exited(status),
+                Err(_) => AttemptEnd::TimedOut,
+            },
+            None => AttemptEnd::Exited(child.wait().await),
+        }
+    };
+    let cancelled = async {
+        // A dropped sender never cancels
+        if cancel.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    let end = tokio::select! {
+        end = exited => end,
+        _ = cancelled => AttemptEnd::Cancelled,
+    };
+    if !matches!(end, AttemptEnd::Exited(_)) {
+        if let Some(pid) = pid {
+            process::terminate(pid);
+        }
+        // Give the command the grace period to clean up, like on Ctrl-C
+        if tokio::time::timeout(process::GRACE_PERIOD, child.wait()).await.is_err() {
+            let _ = child.kill().await;
+        }
+    }
+    if let Some(pid) = pid {
+        process::unregister(pid);
+    }
+    let _ = tokio::join!(stdout, stderr);
+    process::halt_if_stopping().await;
+
+    Ok(match end {
+        AttemptEnd::Exited(status) => {
+            let status = status?;
+            if status.success() {
+                TaskStatus::Success
+            } else {
+                TaskStatus::Failed(status.code().unwrap_or(-1))
+            }
+        }
+        AttemptEnd::TimedOut => {
+            output.line(&format!("⏱️  Timed out after {}", summary::format_duration(task.timeout.unwrap_or_default())));
+            TaskStatus::TimedOut
+        }
+        AttemptEnd::Cancelled => {
+            output.line("⏹️  Stopped");
+            TaskStatus::Cancelled
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_depends_on_expands_same_level_and_upstream_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "knot.yml", "name: demo\n");
+        write(root, "packages/utils/package.yml", "name: utils\nversion: 1.0.0\nscripts:\n  build: tsc\n");
+        write(
+            root,
+            "apps/web/app.yml",
+            "name: web\npackages:\n  - utils\nscripts:\n  lint: eslint src\n  test:\n    run: vitest\n    cwd: src\n    env:\n      CI: \"1\"\n    depends_on: [lint, ^build]\n",
+        );
+
+        let project = Project::find_and_load(root).unwrap();
+        let graph = ProjectGraph::from_project(&project);
+        let tasks = collect_script_tasks(&project, &graph, &graph.all_nodes(), "test", &[]).unwrap();
+
+        let labels: Vec<&str> = tasks.iter().map(|task| task.label()).collect();
+        assert_eq!(labels[0], "utils:build");
+        assert_eq!(tasks.len(), 3);
+        assert!(labels.contains(&"web:lint") && labels.contains(&"web"));
+
+        let test = tasks.iter().find(|task| task.script == "test").unwrap();
+        assert!(test.working_dir.ends_with("apps/web/src"));
+        assert_eq!(test.env.get("CI").map(String::as_str), Some("1"));
+        assert!(test.depends_on.contains(&(ProjectNode::Package("utils".to_string()), "build".to_string())));
+    }
+
+    #[test]
+    fn test_selected_script_keeps_upstream_tasks_of_unselected_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "knot.yml", "name: demo\n");
+        write(root, "packages/utils/package.yml", "name: utils\nversion: 1.0.0\nscripts:\n  build: tsc\n");
+        write(
+            root,
+            "apps/web/app.yml",
+            "name: web\npackages:\n  - utils\nscripts:\n  build:\n    run: vite build\n    depends_on: [^build]\n",
+        );
+        write(root, "apps/docs/app.yml", "name: docs\nscripts:\n  build: mkdocs build\n");
+
+        let project = Project::find_and_load(root).unwrap();
+        let graph = ProjectGraph::from_project(&project);
+        let selected = crate::filter::Filter::parse(&["web".to_string()])
+            .unwrap()
+            .select(&project, &graph)
+            .unwrap();
+        let tasks = collect_script_tasks(&project, &graph, &selected, "build", &[]).unwrap();
+
+        let labels: Vec<&str> = tasks.iter().map(|task| task.label()).collect();
+        assert_eq!(labels, vec!["utils", "web"]);
+        let web = tasks.iter().find(|task| task.label() == "web").unwrap();
+        assert!(web.depends_on.contains(&(ProjectNode::Package("utils".to_string()), "build".to_string())));
+    }
+
+    #[test]
+    fn test_hooks_run_around_script() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "knot.yml", "name: demo\n");
+        write(
+            root,
+            "apps/web/app.yml",
+            "name: web\nscripts:\n  prebuild: echo pre\n  build: echo build\n  postbuild: echo post\n",
+        );
+
+        let project = Project::find_and_load(root).unwrap();
+        let graph = ProjectGraph::from_project(&project);
+        let tasks = collect_script_tasks(&project, &graph, &graph.all_nodes(), "build", &[]).unwrap();
+        let id = |script: &str| (ProjectNode::App("web".to_string()), script.to_string());
+        let task = |script: &str| tasks.iter().find(|task| task.script == script).unwrap();
+
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(task("build").depends_on, vec![id("prebuild")]);
+        assert_eq!(task("postbuild").depends_on, vec![id("build")]);
+        assert!(task("prebuild").depends_on.is_empty());
+    }
+
+    #[test]
+    fn test_circular_depends_on_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "knot.yml", "name: demo\n");
+        write(
+            root,
+            "apps/web/app.yml",
+            "name: web\nscripts:\n  a:\n    run: echo a\n    depends_on: [b]\n  b:\n    run: echo b\n    depends_on: [a]\n",
+        );
+
+        let project = Project::find_and_load(root).unwrap();
+        let graph = ProjectGraph::from_project(&project);
+        let error = collect_script_tasks(&project, &graph, &graph.all_nodes(), "a", &[]).unwrap_err().to_string();
+        assert!(error.contains("Circular depends_on"));
+    }
+
+    #[test]
+    fn test_args_are_escaped_and_substituted() {
+        let args = vec!["--watch".to_string(), "it's fine".to_string(), "$HOME".to_string()];
+        assert_eq!(with_args("vitest", &args, Some("bash")), "vitest --watch 'it'\\''s fine' '$HOME'");
+        assert_eq!(with_args("vitest {{args}} src", &args[..1], None), "vitest --watch src");
+        assert_eq!(with_args("vitest {{args}}", &[], None), "vitest ");
+        assert_eq!(with_args("jest", &args[1..2], Some("pwsh")), "jest 'it''s fine'");
+        assert_eq!(with_args("jest", &args[1..2], Some("cmd")), "jest \"it's fine\"");
+    }
+}
@@ -0,0 +1,122 @@
+use console::{style, Color};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::task::JoinHandle;
+
+const PREFIX_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::Red,
+];
+
+/// How the output of concurrently running tasks is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Print lines as they arrive, prefixed with the task name
+    Interleaved,
+    /// Hold each task's output and print it in one block when the task finishes
+    Buffered,
+    /// Full-screen dashboard with a task list and a log per task; interleaved when stdout
+    /// is not a terminal
+    Tui,
+}
+
+impl OutputMode {
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "interleaved" => Ok(OutputMode::Interleaved),
+            "buffered" => Ok(OutputMode::Buffered),
+            "tui" => Ok(OutputMode::Tui),
+            other => anyhow::bail!(
+                "Unknown output mode '{}'\n💡 Use 'interleaved', 'buffered' or 'tui'",
+                other
+            ),
+        }
+    }
+}
+
+/// Prefixes each output line of a task with its padded, colored name
+#[derive(Debug, Clone)]
+pub struct TaskOutput {
+    prefix: String,
+    buffer: Option<Arc<Mutex<Vec<String>>>>,
+    /// Unprefixed lines shown by the dashboard instead of printing them
+    capture: Option<Arc<Mutex<Vec<String>>>>,
+    /// Unprefixed lines from the child process, stored in the task cache for replay
+    log: Arc<Mutex<String>>,
+}
+
+impl TaskOutput {
+    pub fn new(label: &str, index: usize, width: usize, mode: OutputMode) -> Self {
+        let color = PREFIX_COLORS[index % PREFIX_COLORS.len()];
+        Self {
+            prefix: style(format!("{:width$} │", label, width = width)).fg(color).to_string(),
+            buffer: (mode == OutputMode::Buffered).then(Default::default),
+            capture: None,
+            log: Default::default(),
+        }
+    }
+
+    /// Collect lines into `lines` instead of printing them
+    pub fn with_capture(mut self, lines: Arc<Mutex<Vec<String>>>) -> Self {
+        self.capture = Some(lines);
+        self
+    }
+
+    pub fn line(&self, line: &str) {
+        if let Some(capture) = &self.capture {
+            capture.lock().unwrap().push(line.to_string());
+            return;
+        }
+        let line = format!("{} {}", self.prefix, line);
+        match &self.buffer {
+            Some(buffer) => buffer.lock().unwrap().push(line),
+            None => println!("{}", line),
+        }
+    }
+
+    /// Print everything held back in buffered mode
+    pub fn flush(&self) {
+        if let Some(buffer) = &self.buffer {
+            for line in buffer.lock().unwrap().drain(..) {
+                println!("{}", line);
+            }
+        }
+    }
+
+    /// Everything the child process printed so far
+    pub fn log(&self) -> String {
+        self.log.lock().unwrap().clone()
+    }
+
+    /// Print every line of a child stream until it closes
+    pub fn forward<R>(&self, stream: Option<R>) -> JoinHandle<()>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        self.forward_with(stream, |_| {})
+    }
+
+    /// Like [`TaskOutput::forward`], also passing each line to `on_line`
+    pub fn forward_with<R, F>(&self, stream: Option<R>, on_line: F) -> JoinHandle<()>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        F: Fn(&str) + Send + 'static,
+    {
+        let output = self.clone();
+        tokio::spawn(async move {
+            let Some(stream) = stream else { return };
+            let mut lines = BufReader::new(stream).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                output.line(&line);
+                on_line(&line);
+                let mut log = output.log.lock().unwrap();
+                log.push_str(&line);
+                log.push('\n');
+            }
+        })
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long stopped scripts get to exit cleanly before they are killed
+pub const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Every running script, so they can all be stopped on Ctrl-C: its pid, and whether it leads
+/// its own process group (see [`isolate`]) or shares the terminal's
+static RUNNING: Mutex<BTreeMap<u32, bool>> = Mutex::new(BTreeMap::new());
+
+/// Set once knot was asked to stop, see [`stop_all`]
+static STOPPING: AtomicBool = AtomicBool::new(false);
+
+/// Signal sent to stop a script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ctrl-C (SIGINT)
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+    /// SIGKILL, which cannot be caught
+    Kill,
+}
+
+impl Signal {
+    /// Exit status of knot when it is stopped by this signal, like a shell reports it
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Signal::Interrupt => 130,
+            Signal::Terminate => 143,
+            Signal::Kill => 137,
+        }
+    }
+
+    #[cfg(unix)]
+    fn number(self) -> libc::c_int {
+        match self {
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+/// Start the command in its own process group so the whole tree it spawns can be killed
+pub fn isolate(command: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    command.process_group(0);
+    command.kill_on_drop(true);
+}
+
+/// Track a script started with [`isolate`]
+pub fn register(pid: u32) {
+    if let Ok(mut running) = RUNNING.lock() {
+        running.insert(pid, true);
+    }
+}
+
+/// Track a script sharing knot's process group, e.g. to read from the terminal. Ctrl-C
+/// reaches it directly, other signals are forwarded to it alone.
+pub fn register_foreground(pid: u32) {
+    if let Ok(mut running) = RUNNING.lock() {
+        running.insert(pid, false);
+    }
+}
+
+pub fn unregister(pid: u32) {
+    if let Ok(mut running) = RUNNING.lock() {
+        running.remove(&pid);
+    }
+}
+
+pub fn is_running() -> bool {
+    RUNNING.lock().is_ok_and(|running| !running.is_empty())
+}
+
+/// Whether [`stop_all`] was called; scripts failing from then on were stopped on purpose
+pub fn stopping() -> bool {
+    STOPPING.load(Ordering::SeqCst)
+}
+
+/// Never returns once knot is stopping, so nothing runs after scripts were interrupted; the
+/// signal handler exits the process
+pub async fn halt_if_stopping() {
+    if stopping() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Stop every running script: send `signal` (Ctrl-C already reached the foreground ones), wait
+/// up to the grace period for them to exit, then kill what is left
+pub fn stop_all(signal: Signal) {
+    STOPPING.store(true, Ordering::SeqCst);
+    let running: Vec<(u32, bool)> = match RUNNING.lock() {
+        Ok(running) => running.iter().map(|(pid, group)| (*pid, *group)).collect(),
+        Err(_) => return,
+    };
+    stop(&running, signal);
+}
+
+fn stop(running: &[(u32, bool)], signal: Signal) {
+    for &(pid, group) in running {
+        if group || signal != Signal::Interrupt {
+            send(pid, group, signal);
+        }
+    }
+    let deadline = Instant::now() + GRACE_PERIOD;
+    while running.iter().any(|&(pid, group)| is_alive(pid, group)) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    for &(pid, group) in running {
+        if is_alive(pid, group) {
+            send(pid, group, Signal::Kill);
+        }
+    }
+}
+
+/// Kill every running script right away, e.g. after a second Ctrl-C
+pub fn kill_all() {
+    if let Ok(running) = RUNNING.lock() {
+        for (pid, group) in running.iter() {
+            send(*pid, *group, Signal::Kill);
+        }
+    }
+}
+
+/// Stop a script along with every process it spawned when it was started with [`isolate`]:
+/// SIGTERM first, SIGKILL for whatever still runs after the grace period
+pub fn terminate(pid: u32) {
+    let group = RUNNING.lock().map_or(true, |running| running.get(&pid).copied().unwrap_or(true));
+    send(pid, group, Signal::Terminate);
+    #[cfg(unix)]
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + GRACE_PERIOD;
+        while is_alive(pid, group) {
+            if Instant::now() >= deadline {
+                send(pid, group, Signal::Kill);
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    });
+}
+
+fn send(pid: u32, group: bool, signal: Signal) {
+    #[cfg(unix)]
+    unsafe {
+        if group {
+            libc::killpg(pid as libc::pid_t, signal.number());
+        } else {
+            libc::kill(pid as libc::pid_t, signal.number());
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        // Windows has no signals to forward: the whole tree is terminated
+        let _ = (group, signal);
+        let _ = std::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .status();
+    }
+}
+
+/// Whether the process (or any process of the group) still exists; exited processes that were
+/// not reaped yet count as alive
+fn is_alive(pid: u32, group: bool) -> bool {
+    #[cfg(unix)]
+    unsafe {
+        let result = if group {
+            libc::killpg(pid as libc::pid_t, 0)
+        } else {
+            libc::kill(pid as libc::pid_t, 0)
+        };
+        result == 0
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (pid, group);
+        false
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stop_forwards_the_signal_to_the_group() {
+        // The shell's child is in the script's own process group, which the terminal's Ctrl-C
+        // does not reach
+        let mut command = tokio::process::Command::new("sh");
+        command.args(["-c", "sleep 30; echo done"]);
+        isolate(&mut command);
+        let mut child = command.spawn().unwrap();
+        let pid = child.id().unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let start = Instant::now();
+        let stopper = std::thread::spawn(move || stop(&[(pid, true)], Signal::Interrupt));
+        let status = child.wait().await.unwrap();
+        stopper.join().unwrap();
+
+        assert!(!status.success());
+        assert!(start.elapsed() < GRACE_PERIOD);
+        assert!(!is_alive(pid, true));
+    }
+}
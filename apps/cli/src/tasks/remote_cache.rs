@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+use crate::config::RemoteCacheConfig;
+
+/// Environment variable holding the bearer token when none is configured
+pub const TOKEN_ENV: &str = "KNOT_REMOTE_CACHE_TOKEN";
+
+/// Client for a shared task cache server storing `<hash>` tarballs at `/cache/<hash>`
+#[derive(Debug, Clone)]
+pub struct RemoteTaskCache {
+    base_url: String,
+    token: Option<String>,
+    read_only: bool,
+    client: reqwest::Client,
+}
+
+impl RemoteTaskCache {
+    pub fn new(config: &RemoteCacheConfig) -> Self {
+        Self {
+            base_url: config.url.trim_end_matches('/').to_string(),
+            token: config.token.clone().or_else(|| std::env::var(TOKEN_ENV).ok()),
+            read_only: config.read_only,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(60))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn url(&self, hash: &str) -> String {
+        format!("{}/cache/{}", self.base_url, hash)
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Download the tarball stored under `hash`, or `None` if the server does not have it
+    pub async fn get(&self, hash: &str) -> Result<Option<Vec<u8>>> {
+        let url = self.url(hash);
+        let response = self
+            .authorize(self.client.get(&url))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach remote task cache at {}", self.base_url))?;
+
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+            status => anyhow::bail!(
+                "Remote task cache answered {} for {}\n💡 Check remote_cache.url and the token in knot.yml, ~/.knot/config.yml or {}",
+                status, url, TOKEN_ENV
+            ),
+        }
+    }
+
+    /// Upload the tarball for `hash`
+    pub async fn put(&self, hash: &str, archive: Vec<u8>) -> Result<()> {
+        let url = self.url(hash);
+        let response = self
+            .authorize(self.client.put(&url))
+            .header(reqwest::header::CONTENT_TYPE, "application/gzip")
+            .body(archive)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach remote task cache at {}", self.base_url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Remote task cache rejected upload to {} ({})\n💡 Set remote_cache.read_only: true on machines that should only download",
+                url,
+                response.status()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ProjectNode;
+    use crate::tasks::cache::TaskCache;
+    use crate::tasks::Task;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    type Blobs = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Minimal cache server: stores PUT bodies in memory and requires `Bearer secret`
+    fn serve() -> (String, Blobs) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let blobs = Blobs::default();
+        let stored = blobs.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap_or("").to_string(), parts.next().unwrap_or("").to_string());
+
+                let (mut content_length, mut authorized) = (0, false);
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim().to_lowercase();
+                    if header.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = header.strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                    authorized |= header == "authorization: bearer secret";
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let (status, response_body) = match (authorized, method.as_str()) {
+                    (false, _) => ("401 Unauthorized", Vec::new()),
+                    (true, "PUT") => {
+                        stored.lock().unwrap().insert(path, body);
+                        ("200 OK", Vec::new())
+                    }
+                    (true, _) => match stored.lock().unwrap().get(&path) {
+                        Some(blob) => ("200 OK", blob.clone()),
+                        None => ("404 Not Found", Vec::new()),
+                    },
+                };
+                let head = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    response_body.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(&response_body).unwrap();
+            }
+        });
+
+        (base_url, blobs)
+    }
+
+    fn remote(url: &str, token: &str, read_only: bool) -> RemoteTaskCache {
+        RemoteTaskCache::new(&RemoteCacheConfig {
+            url: url.to_string(),
+            token: Some(token.to_string()),
+            read_only,
+        })
+    }
+
+    fn task(dir: &Path) -> Task {
+        Task {
+            outputs: vec!["dist".to_string()],
+            ..Task::new(ProjectNode::Package("utils".to_string()), "build", "tsc", dir.to_path_buf())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ci_run_warms_cache_for_other_machines() {
+        let (url, blobs) = serve();
+
+        // CI builds and uploads
+        let ci = TempDir::new().unwrap();
+        std::fs::create_dir_all(ci.path().join("dist")).unwrap();
+        std::fs::write(ci.path().join("dist/index.js"), "built").unwrap();
+        let ci_cache = TaskCache::new(ci.path().join(".knot/cache/tasks")).with_remote(remote(&url, "secret", false));
+        ci_cache.store("abc", &task(ci.path()), "built on ci\n").await.unwrap();
+        assert!(blobs.lock().unwrap().contains_key("/cache/abc"));
+
+        // A laptop with an empty local cache downloads it
+        let laptop = TempDir::new().unwrap();
+        let laptop_cache =
+            TaskCache::new(laptop.path().join(".knot/cache/tasks")).with_remote(remote(&url, "secret", true));
+        let hit = laptop_cache.restore("abc", &task(laptop.path())).await.unwrap().unwrap();
+        assert!(hit.remote);
+        assert_eq!(hit.log, "built on ci\n");
+        assert_eq!(std::fs::read_to_string(laptop.path().join("dist/index.js")).unwrap(), "built");
+        assert!(laptop_cache.restore("missing", &task(laptop.path())).await.unwrap().is_none());
+
+        // Read-only caches never upload, and a wrong token is reported
+        laptop_cache.store("def", &task(laptop.path()), "").await.unwrap();
+        assert!(!blobs.lock().unwrap().contains_key("/cache/def"));
+        let error = remote(&url, "wrong", false).get("abc").await.unwrap_err().to_string();
+        assert!(error.contains("401"));
+    }
+}
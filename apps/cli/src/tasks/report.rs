@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use super::summary::{exit_code, status_label};
+use super::TaskResult;
+
+/// Write a Chrome trace (viewable in Perfetto or chrome://tracing) with one slice per task
+/// that ran, on the concurrency lane it ran in, timed from `run_start`
+pub fn write_trace(path: &Path, results: &[TaskResult], run_start: Instant) -> Result<()> {
+    write_json(path, &trace(results, run_start))
+}
+
+/// Write a JSON summary of every task: its status, duration, exit code and attempts
+pub fn write_report(path: &Path, results: &[TaskResult]) -> Result<()> {
+    write_json(path, &report(results))
+}
+
+fn trace(results: &[TaskResult], run_start: Instant) -> Value {
+    let mut events = vec![json!({
+        "name": "process_name",
+        "ph": "M",
+        "pid": 1,
+        "args": { "name": "knot" },
+    })];
+
+    let mut lanes: Vec<usize> = results.iter().filter_map(|result| result.lane).collect();
+    lanes.sort_unstable();
+    lanes.dedup();
+    for lane in lanes {
+        events.push(json!({
+            "name": "thread_name",
+            "ph": "M",
+            "pid": 1,
+            "tid": lane + 1,
+            "args": { "name": format!("lane {}", lane + 1) },
+        }));
+    }
+
+    for result in results {
+        let (Some(started), Some(lane)) = (result.started, result.lane) else {
+            continue;
+        };
+        events.push(json!({
+            "name": result.task.label(),
+            "cat": if result.cached { "cache" } else { result.task.script.as_str() },
+            "ph": "X",
+            "ts": started.saturating_duration_since(run_start).as_micros() as u64,
+            "dur": result.duration.as_micros() as u64,
+            "pid": 1,
+            "tid": lane + 1,
+            "args": {
+                "command": result.task.command,
+                "status": status_label(result),
+                "exit_code": exit_code(result),
+                "attempts": result.attempts,
+                "cached": result.cached,
+                "hash": result.hash,
+            },
+        }));
+    }
+
+    json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+}
+
+fn report(results: &[TaskResult]) -> Value {
+    let tasks: Vec<Value> = results
+        .iter()
+        .map(|result| {
+            json!({
+                "task": result.task.label(),
+                "script": result.task.script,
+                "target": result.task.node.name(),
+                "kind": result.task.node.kind(),
+                "status": status_label(result),
+                "duration_ms": result.duration.as_millis() as u64,
+                "exit_code": exit_code(result),
+                "attempts": result.attempts,
+                "cached": result.cached,
+            })
+        })
+        .collect();
+
+    json!({
+        "success": results.iter().all(TaskResult::is_success),
+        "exit_code": super::worst_exit_code(results),
+        "tasks": tasks,
+    })
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, serde_json::to_string_pretty(value)?).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ProjectNode;
+    use crate::tasks::{Task, TaskStatus};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn result(name: &str, status: TaskStatus, lane: Option<usize>, run_start: Instant) -> TaskResult {
+        TaskResult {
+            task: Task::new(ProjectNode::Package(name.to_string()), "build", "make", PathBuf::from(".")),
+            status,
+            duration: Duration::from_millis(1500),
+            hash: None,
+            cached: false,
+            attempts: u32::from(lane.is_some()),
+            started: lane.map(|_| run_start + Duration::from_millis(250)),
+            lane,
+        }
+    }
+
+    #[test]
+    fn test_trace_and_report() {
+        let run_start = Instant::now();
+        let results = vec![
+            result("utils", TaskStatus::Success, Some(0), run_start),
+            result("ui", TaskStatus::Failed(2), Some(1), run_start),
+            result("web", TaskStatus::Skipped, None, run_start),
+        ];
+
+        let trace = trace(&results, run_start);
+        let slices: Vec<&Value> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .collect();
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[1]["name"], "ui");
+        assert_eq!(slices[1]["ts"], 250_000);
+        assert_eq!(slices[1]["dur"], 1_500_000);
+        assert_eq!(slices[1]["tid"], 2);
+
+        let report = report(&results);
+        assert_eq!(report["exit_code"], 2);
+        assert_eq!(report["tasks"][0]["status"], "passed");
+        assert_eq!(report["tasks"][1]["exit_code"], 2);
+        assert_eq!(report["tasks"][2]["status"], "skipped");
+        assert!(report["tasks"][2]["exit_code"].is_null());
+    }
+}
@@ -0,0 +1,473 @@
+use anyhow::Result;
+use console::style;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use super::cache::TaskCache;
+use super::dashboard::{Dashboard, DashboardCommand};
+use super::output::{OutputMode, TaskOutput};
+use super::report;
+use super::{run_task, Task, TaskId, TaskResult, TaskStatus};
+use crate::graph::{ProjectGraph, ProjectNode};
+
+#[derive(Debug, Clone)]
+pub struct SchedulerOptions {
+    pub concurrency: usize,
+    pub output_mode: OutputMode,
+    /// Restore and store cacheable tasks (disabled by `--no-cache`)
+    pub use_cache: bool,
+    pub on_failure: FailurePolicy,
+    /// Chrome trace of the run to write (`--profile-out`)
+    pub profile_out: Option<PathBuf>,
+    /// JSON summary of the run to write (`--report-json`)
+    pub report_json: Option<PathBuf>,
+}
+
+/// What happens to other tasks once a task fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailurePolicy {
+    /// Skip the tasks depending on the failed one, run the rest (`--continue-on-error`)
+    #[default]
+    SkipDependents,
+    /// Start no further tasks and stop the running ones (`--bail`)
+    Bail,
+}
+
+impl Default for SchedulerOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: default_concurrency(),
+            output_mode: OutputMode::Interleaved,
+            use_cache: true,
+            on_failure: FailurePolicy::default(),
+            profile_out: None,
+            report_json: None,
+        }
+    }
+}
+
+pub fn default_concurrency() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+}
+
+/// Run tasks concurrently, starting each one once the tasks it depends on have succeeded, and
+/// return their results in the order of `tasks`. Tasks downstream of a failure are skipped
+/// unless the failure policy continues past it or bails out entirely. Cacheable tasks whose
+/// dependencies were all hashed are restored from `cache` on a hit. With the `tui` output mode
+/// on a terminal, the tasks are shown in a [`Dashboard`] where they can be restarted or
+/// stopped, and the run ends when it is quit.
+pub async fn run_tasks(
+    tasks: &[Task],
+    graph: &ProjectGraph,
+    cache: Option<&TaskCache>,
+    options: &SchedulerOptions,
+) -> Result<Vec<TaskResult>> {
+    let dependencies = task_dependencies(tasks, graph);
+    let width = tasks.iter().map(|task| task.label().len()).max().unwrap_or(0);
+    let concurrency = options.concurrency.max(1);
+
+    let mut output_mode = options.output_mode;
+    let mut dashboard = None;
+    if output_mode == OutputMode::Tui {
+        output_mode = OutputMode::Interleaved;
+        if console::Term::stdout().is_term() && !tasks.is_empty() {
+            let labels: Vec<&str> = tasks.iter().map(Task::label).collect();
+            dashboard = Some(Dashboard::start(&labels)?);
+        }
+    }
+
+    let mut results: Vec<Option<TaskResult>> = vec![None; tasks.len()];
+    let mut started = vec![false; tasks.len()];
+    let mut running = JoinSet::new();
+    // Stops a running task; also used to restart it from the dashboard
+    let mut cancels: Vec<Option<watch::Sender<bool>>> = vec![None; tasks.len()];
+    let mut restarting = vec![false; tasks.len()];
+    let mut bailed = false;
+    let mut quit = false;
+    let run_start = Instant::now();
+    let mut busy_lanes = BTreeSet::new();
+
+    loop {
+        for (index, deps) in dependencies.iter().enumerate() {
+            if started[index] {
+                continue;
+            }
+
+            let failed_dep = deps
+                .iter()
+                .any(|dep| results[*dep].as_ref().is_some_and(|result| !result.is_success()));
+            if failed_dep || bailed || quit {
+                started[index] = true;
+                let result = skipped(&tasks[index]);
+                if let Some(dashboard) = &dashboard {
+                    dashboard.finished(index, &result);
+                }
+                results[index] = Some(result);
+                continue;
+            }
+
+            let ready = deps
+                .iter()
+                .all(|dep| results[*dep].as_ref().is_some_and(TaskResult::is_success));
+            if ready && running.len() < concurrency {
+                started[index] = true;
+                let task = tasks[index].clone();
+                let mut output = TaskOutput::new(task.label(), index, width, output_mode);
+                if let Some(dashboard) = &dashboard {
+                    dashboard.started(index);
+                    output = output.with_capture(dashboard.lines(index));
+                }
+                output.line(&style(format!("$ {}", task.command)).dim().to_string());
+
+                // A dependency that was not hashed may have changed without us knowing
+                let dependency_hashes: Option<Vec<String>> = deps
+                    .iter()
+                    .map(|dep| results[*dep].as_ref().and_then(|result| result.hash.clone()))
+                    .collect();
+                let cache = cache
+                    .filter(|_| task.is_cacheable())
+                    .cloned()
+                    .zip(dependency_hashes);
+                let (cancel, cancelled) = watch::channel(false);
+                cancels[index] = Some(cancel);
+                let lane = (0..).find(|lane| !busy_lanes.contains(lane)).unwrap_or_default();
+                busy_lanes.insert(lane);
+
+                running.spawn(async move {
+                    let started = Instant::now();
+                    let mut result = match run_cached_task(&task, &output, cache, cancelled).await {
+                        Ok(result) => result,
+                        Err(e) => {
+                            output.line(&style(format!("{:#}", e)).red().to_string());
+                            TaskResult {
+                                task: task.clone(),
+                                status: TaskStatus::Failed(-1),
+                                duration: Duration::ZERO,
+                                hash: None,
+                                cached: false,
+                                attempts: 1,
+                                started: None,
+                                lane: None,
+                            }
+                        }
+                    };
+                    result.started = Some(started);
+                    result.lane = Some(lane);
+                    output.flush();
+                    (index, result)
+                });
+            }
+        }
+
+        if quit && running.is_empty() {
+            break;
+        }
+
+        // Without a dashboard this ends once nothing is running; with one, when it is quit
+        let event = tokio::select! {
+            Some(joined) = running.join_next() => {
+                let (index, result) = joined?;
+                SchedulerEvent::Finished(index, Box::new(result))
+            }
+            Some(command) = next_command(&mut dashboard) => SchedulerEvent::Command(command),
+            else => break,
+        };
+
+        match event {
+            SchedulerEvent::Finished(index, result) => {
+                if let Some(lane) = result.lane {
+                    busy_lanes.remove(&lane);
+                }
+                cancels[index] = None;
+                if std::mem::take(&mut restarting[index]) && !quit {
+                    started[index] = false;
+                    continue;
+                }
+                if let Some(dashboard) = &dashboard {
+                    dashboard.finished(index, &result);
+                }
+                if options.on_failure == FailurePolicy::Bail && matches!(result.status, TaskStatus::Failed(_) | TaskStatus::TimedOut) {
+                    bailed = true;
+                    cancels.iter().flatten().for_each(|cancel| {
+                        cancel.send_replace(true);
+                    });
+                }
+                results[index] = Some(*result);
+            }
+            SchedulerEvent::Command(DashboardCommand::Kill(index)) => {
+                if let Some(cancel) = &cancels[index] {
+                    cancel.send_replace(true);
+                }
+            }
+            SchedulerEvent::Command(DashboardCommand::Restart(index)) => {
+                if let Some(cancel) = &cancels[index] {
+                    restarting[index] = true;
+                    cancel.send_replace(true);
+                } else if results[index].is_some() {
+                    // Rerun the task, and the tasks skipped because of it or of a bail
+                    bailed = false;
+                    for other in 0..tasks.len() {
+                        let rerun = other == index
+                            || results[other].as_ref().is_some_and(|result| result.status == TaskStatus::Skipped);
+                        if rerun {
+                            results[other] = None;
+                            started[other] = false;
+                            if let Some(dashboard) = &dashboard {
+                                dashboard.waiting(other);
+                            }
+                        }
+                    }
+                }
+            }
+            SchedulerEvent::Command(DashboardCommand::Quit) => {
+                quit = true;
+                cancels.iter().flatten().for_each(|cancel| {
+                    cancel.send_replace(true);
+                });
+            }
+        }
+    }
+
+    // Anything never started was waiting on a failed task
+    let results: Vec<TaskResult> = results
+        .into_iter()
+        .zip(tasks)
+        .map(|(result, task)| result.unwrap_or_else(|| skipped(task)))
+        .collect();
+
+    if let Some(dashboard) = dashboard {
+        let logs: Vec<_> = (0..tasks.len()).map(|index| dashboard.lines(index)).collect();
+        drop(dashboard);
+        // The dashboard takes its logs along when it closes, so print those of the failed tasks
+        for (index, result) in results.iter().enumerate() {
+            if matches!(result.status, TaskStatus::Failed(_) | TaskStatus::TimedOut) {
+                let output = TaskOutput::new(result.task.label(), index, width, OutputMode::Interleaved);
+                logs[index].lock().unwrap().iter().for_each(|line| output.line(line));
+            }
+        }
+    }
+
+    if let Some(path) = &options.profile_out {
+        report::write_trace(path, &results, run_start)?;
+        println!("⏱️  Wrote timeline to {} (open in ui.perfetto.dev or chrome://tracing)", path.display());
+    }
+    if let Some(path) = &options.report_json {
+        report::write_report(path, &results)?;
+        println!("📝 Wrote report to {}", path.display());
+    }
+    Ok(results)
+}
+
+enum SchedulerEvent {
+    Finished(usize, Box<TaskResult>),
+    Command(DashboardCommand),
+}
+
+/// Next key command of the dashboard, if there is one
+async fn next_command(dashboard: &mut Option<Dashboard>) -> Option<DashboardCommand> {
+    match dashboard {
+        Some(dashboard) => dashboard.next_command().await,
+        None => None,
+    }
+}
+
+fn skipped(task: &Task) -> TaskResult {
+    TaskResult {
+        task: task.clone(),
+        status: TaskStatus::Skipped,
+        duration: Duration::ZERO,
+        hash: None,
+        cached: false,
+        attempts: 0,
+        started: None,
+        lane: None,
+    }
+}
+
+/// Replay a cached run of the task if there is one, otherwise run it and cache its outputs
+async fn run_cached_task(
+    task: &Task,
+    output: &TaskOutput,
+    cache: Option<(TaskCache, Vec<String>)>,
+    cancel: watch::Receiver<bool>,
+) -> Result<TaskResult> {
+    let Some((cache, dependency_hashes)) = cache else {
+        return run_task(task, output, cancel).await;
+    };
+
+    let start = Instant::now();
+    let hash = TaskCache::hash(task, &dependency_hashes)?;
+    let short_hash = &hash[..12];
+    match cache.restore(&hash, task).await {
+        Ok(Some(hit)) => {
+            let source = if hit.remote { "remote cache hit" } else { "cache hit" };
+            output.line(&style(format!("{}, replaying logs ({})", source, short_hash)).green().to_string());
+            for line in hit.log.lines() {
+                output.line(line);
+            }
+            return Ok(TaskResult {
+                task: task.clone(),
+                status: TaskStatus::Success,
+                duration: start.elapsed(),
+                hash: Some(hash),
+                cached: true,
+                attempts: 0,
+                started: Some(start),
+                lane: None,
+            });
+        }
+        Ok(None) => {
+            output.line(&style(format!("cache miss, executing ({})", short_hash)).dim().to_string());
+        }
+        Err(e) => {
+            output.line(&style(format!("⚠️  Could not read the task cache, executing: {:#}", e)).yellow().to_string());
+        }
+    }
+
+    let mut result = run_task(task, output, cancel).await?;
+    if result.is_success() {
+        if let Err(e) = cache.store(&hash, task, &output.log()).await {
+            output.line(&style(format!("⚠️  Could not cache outputs: {:#}", e)).yellow().to_string());
+        }
+        result.hash = Some(hash);
+    }
+    Ok(result)
+}
+
+/// For each task, the tasks listed in its `depends_on` plus the same script of the nearest
+/// upstream packages (`^build` semantics): nodes without that script are looked through to
+/// their dependencies.
+fn task_dependencies(tasks: &[Task], graph: &ProjectGraph) -> Vec<Vec<usize>> {
+    let index: HashMap<TaskId, usize> = tasks.iter().enumerate().map(|(i, task)| (task.id(), i)).collect();
+
+    tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| {
+            let mut deps: BTreeSet<usize> = task.depends_on.iter().filter_map(|id| index.get(id).copied()).collect();
+            let mut visited = HashSet::new();
+            let mut stack: Vec<&ProjectNode> = graph.dependencies_of(&task.node).collect();
+
+            while let Some(node) = stack.pop() {
+                if !visited.insert(node) {
+                    continue;
+                }
+                match index.get(&(node.clone(), task.script.clone())) {
+                    Some(&dep) => {
+                        deps.insert(dep);
+                    }
+                    None => stack.extend(graph.dependencies_of(node)),
+                }
+            }
+
+            // Tasks depending on a script also wait for its post hook
+            let post_hooks: Vec<usize> = deps
+                .iter()
+                .filter_map(|&dep| index.get(&(tasks[dep].node.clone(), super::post_hook(&tasks[dep].script))))
+                .copied()
+                .filter(|&post| post != i)
+                .collect();
+            deps.extend(post_hooks);
+
+            deps.into_iter().collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::worst_exit_code;
+    use tempfile::TempDir;
+
+    fn task(node: ProjectNode, command: &str, dir: &TempDir) -> Task {
+        Task::new(node, "build", command, dir.path().to_path_buf())
+    }
+
+    #[tokio::test]
+    async fn test_dependents_wait_and_failures_skip_downstream() {
+        let dir = TempDir::new().unwrap();
+        let package = |name: &str| ProjectNode::Package(name.to_string());
+        let web = ProjectNode::App("web".to_string());
+        let graph = ProjectGraph::from_edges(&[
+            (package("types"), &[]),
+            (package("utils"), &[package("types")]),
+            (package("broken"), &[]),
+            (web.clone(), &[package("utils"), package("broken")]),
+        ]);
+
+        let tasks = vec![
+            task(package("types"), "sleep 0.2 && touch types.done", &dir),
+            task(package("utils"), "test -f types.done", &dir),
+            task(package("broken"), "exit 2", &dir),
+            task(web, "touch web.done", &dir),
+        ];
+        let options = SchedulerOptions {
+            concurrency: 4,
+            output_mode: OutputMode::Buffered,
+            use_cache: false,
+            on_failure: FailurePolicy::SkipDependents,
+            ..SchedulerOptions::default()
+        };
+
+        let results = run_tasks(&tasks, &graph, None, &options).await.unwrap();
+        let statuses: Vec<_> = results.iter().map(|r| r.status.clone()).collect();
+
+        assert_eq!(
+            statuses,
+            vec![TaskStatus::Success, TaskStatus::Success, TaskStatus::Failed(2), TaskStatus::Skipped]
+        );
+        assert!(!dir.path().join("web.done").exists());
+        assert_eq!(worst_exit_code(&results), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_and_failure_policies() {
+        let dir = TempDir::new().unwrap();
+        let package = |name: &str| ProjectNode::Package(name.to_string());
+        let graph = ProjectGraph::from_edges(&[
+            (package("flaky"), &[]),
+            (package("broken"), &[]),
+            (package("slow"), &[]),
+            (package("dependent"), &[package("broken")]),
+        ]);
+
+        let mut flaky = task(package("flaky"), "test -f tried || { touch tried; exit 1; }", &dir);
+        flaky.retries = 2;
+        let tasks = vec![
+            flaky,
+            task(package("broken"), "sleep 0.2 && exit 3", &dir),
+            task(package("slow"), "sleep 5", &dir),
+            task(package("dependent"), "true", &dir),
+        ];
+
+        let mut options = SchedulerOptions {
+            concurrency: 4,
+            output_mode: OutputMode::Buffered,
+            use_cache: false,
+            on_failure: FailurePolicy::Bail,
+            ..SchedulerOptions::default()
+        };
+        let results = run_tasks(&tasks, &graph, None, &options).await.unwrap();
+        assert_eq!(results[0].status, TaskStatus::Success);
+        assert_eq!(results[0].attempts, 2);
+        assert_eq!(results[1].status, TaskStatus::Failed(3));
+        assert_eq!(results[2].status, TaskStatus::Cancelled);
+        assert!(results[2].duration < Duration::from_secs(4));
+        assert_eq!(results[3].status, TaskStatus::Skipped);
+
+        options.on_failure = FailurePolicy::SkipDependents;
+        let tasks = vec![
+            tasks[1].clone(),
+            task(package("slow"), "sleep 0.5", &dir),
+            tasks[3].clone(),
+        ];
+        let results = run_tasks(&tasks, &graph, None, &options).await.unwrap();
+        assert_eq!(results[0].status, TaskStatus::Failed(3));
+        assert_eq!(results[1].status, TaskStatus::Success);
+        assert_eq!(results[2].status, TaskStatus::Skipped);
+    }
+}
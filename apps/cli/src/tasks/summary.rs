@@ -0,0 +1,106 @@
+use console::style;
+use std::time::Duration;
+
+use super::{TaskResult, TaskStatus};
+
+/// Print a pass/fail table for a batch of tasks, with exit codes and attempts
+pub fn print_summary(results: &[TaskResult]) {
+    let width = results
+        .iter()
+        .map(|result| result.task.label().len())
+        .max()
+        .unwrap_or(0)
+        .max("Task".len());
+
+    println!();
+    println!("{}", style("📊 Summary").bold());
+    println!("  {:width$}  {:8}  {:>4}  {:>5}  {:>8}", "Task", "Status", "Exit", "Tries", "Time", width = width);
+    for result in results {
+        let status = style(format!("{:8}", status_label(result)));
+        let status = match result.status {
+            TaskStatus::Success if result.cached => status.cyan(),
+            TaskStatus::Success => status.green(),
+            TaskStatus::Failed(_) | TaskStatus::TimedOut => status.red(),
+            TaskStatus::Skipped | TaskStatus::Cancelled => status.yellow(),
+        };
+        let exit_code = exit_code(result).map_or("-".to_string(), |code| code.to_string());
+        let attempts = if result.attempts > 0 { result.attempts.to_string() } else { "-".to_string() };
+        println!(
+            "  {:width$}  {}  {:>4}  {:>5}  {:>8}",
+            result.task.label(),
+            status,
+            exit_code,
+            attempts,
+            format_duration(result.duration),
+            width = width
+        );
+    }
+
+    let count = |matches: fn(&TaskStatus) -> bool| results.iter().filter(|result| matches(&result.status)).count();
+    let passed = count(|status| *status == TaskStatus::Success);
+    let failed = count(|status| matches!(status, TaskStatus::Failed(_) | TaskStatus::TimedOut));
+    let skipped = count(|status| *status == TaskStatus::Skipped);
+    let stopped = count(|status| *status == TaskStatus::Cancelled);
+    let cached = results.iter().filter(|result| result.cached).count();
+    let retried = results.iter().filter(|result| result.attempts > 1).count();
+
+    print!("\n  {} passed", style(passed).green());
+    if cached > 0 {
+        print!(" ({} from cache)", style(cached).cyan());
+    }
+    print!(
+        ", {} failed",
+        if failed > 0 { style(failed).red() } else { style(failed).dim() }
+    );
+    if skipped > 0 {
+        print!(", {} skipped", style(skipped).yellow());
+    }
+    if stopped > 0 {
+        print!(", {} stopped", style(stopped).yellow());
+    }
+    if retried > 0 {
+        print!(", {} retried", style(retried).yellow());
+    }
+    println!();
+}
+
+/// Status shown in summaries and reports
+pub fn status_label(result: &TaskResult) -> &'static str {
+    match result.status {
+        TaskStatus::Success if result.cached => "cached",
+        TaskStatus::Success => "passed",
+        TaskStatus::Failed(_) => "failed",
+        TaskStatus::TimedOut => "timeout",
+        TaskStatus::Skipped => "skipped",
+        TaskStatus::Cancelled => "stopped",
+    }
+}
+
+/// Exit code of the command's last run, `None` for tasks that did not run it
+pub fn exit_code(result: &TaskResult) -> Option<i32> {
+    match result.status {
+        TaskStatus::Success if !result.cached => Some(0),
+        TaskStatus::Failed(_) | TaskStatus::TimedOut => Some(result.status.exit_code()),
+        _ => None,
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    if duration.as_secs() >= 60 {
+        format!("{}m{:02}s", duration.as_secs() / 60, duration.as_secs() % 60)
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(1250)), "1.2s");
+        assert_eq!(format_duration(Duration::from_secs(59)), "59.0s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::Result;
+use regex::Regex;
+use semver::{Version, VersionReq};
+use std::collections::{BTreeMap, HashMap};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use crate::project::Project;
+
+/// A tool version declared under `tools` in knot.yml or an app.yml
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    /// Executable name, run with `--version`
+    pub tool: String,
+    pub version: VersionReq,
+    /// Where it was declared: `knot.yml` or `app 'web'`
+    pub source: String,
+}
+
+/// Source of the requirements declared in knot.yml
+const PROJECT_SOURCE: &str = "knot.yml";
+
+impl Requirement {
+    /// Declared in knot.yml rather than an app.yml
+    pub fn is_project_wide(&self) -> bool {
+        self.source == PROJECT_SOURCE
+    }
+}
+
+/// What `<tool> --version` revealed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Detected {
+    Found(Version),
+    /// Ran, but printed no version number
+    Unparsed(String),
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolCheck {
+    pub requirement: Requirement,
+    pub detected: Detected,
+}
+
+impl ToolCheck {
+    /// Whether the tool satisfies the requirement; `"*"` only needs it to run, even without a
+    /// version in its output (e.g. `sh --version` on dash)
+    pub fn is_ok(&self) -> bool {
+        match &self.detected {
+            Detected::Found(version) => self.requirement.version.matches(version),
+            Detected::Unparsed(_) => self.requirement.version == VersionReq::STAR,
+            Detected::Missing => false,
+        }
+    }
+
+    /// What was found, e.g. `found 18.19.0` or `not found on PATH`
+    pub fn outcome(&self) -> String {
+        match &self.detected {
+            Detected::Found(version) => format!("found {}", version),
+            Detected::Unparsed(output) => format!("no version in '{} --version' output: {}", self.requirement.tool, output),
+            Detected::Missing => "not found on PATH".to_string(),
+        }
+    }
+}
+
+/// Requirements for scripts of the project, or of `app` when given: the project's `tools`,
+/// with those the app declares replacing the project's for the same tool
+pub fn requirements(project: &Project, app: Option<&str>) -> Result<Vec<Requirement>> {
+    let mut requirements = BTreeMap::new();
+    if let Some(tools) = &project.config.tools {
+        for (tool, version) in tools {
+            requirements.insert(tool.clone(), parse_requirement(tool, version, PROJECT_SOURCE)?);
+        }
+    }
+    if let Some(tools) = app.and_then(|app| project.apps.get(app)).and_then(|config| config.tools.as_ref()) {
+        let source = format!("app '{}'", app.unwrap_or_default());
+        for (tool, version) in tools {
+            requirements.insert(tool.clone(), parse_requirement(tool, version, &source)?);
+        }
+    }
+    Ok(requirements.into_values().collect())
+}
+
+fn parse_requirement(tool: &str, version: &str, source: &str) -> Result<Requirement> {
+    let parsed = VersionReq::parse(version.trim()).map_err(|e| {
+        anyhow::anyhow!(
+            "Invalid version requirement '{}' for tool '{}' in {}: {}\n💡 Use a semver range like \">=20\", \"^1.1\" or \"*\"",
+            version, tool, source, e
+        )
+    })?;
+    Ok(Requirement {
+        tool: tool.to_string(),
+        version: parsed,
+        source: source.to_string(),
+    })
+}
+
+/// Detect the version of every required tool, running each tool once
+pub fn check(requirements: &[Requirement]) -> Vec<ToolCheck> {
+    let mut detected: HashMap<&str, Detected> = HashMap::new();
+    requirements
+        .iter()
+        .map(|requirement| ToolCheck {
+            requirement: requirement.clone(),
+            detected: detected
+                .entry(requirement.tool.as_str())
+                .or_insert_with(|| detect(&requirement.tool))
+                .clone(),
+        })
+        .collect()
+}
+
+/// Fail with every unmet requirement of the project and `apps` before scripts run into them
+pub fn ensure(project: &Project, apps: &[&str]) -> Result<()> {
+    let mut all = if apps.is_empty() { requirements(project, None)? } else { Vec::new() };
+    for app in apps {
+        for requirement in requirements(project, Some(app))? {
+            // Project requirements not replaced by an app are shared by all of them
+            if !all.iter().any(|other| other.tool == requirement.tool && other.source == requirement.source) {
+                all.push(requirement);
+            }
+        }
+    }
+
+    let failed: Vec<ToolCheck> = check(&all).into_iter().filter(|check| !check.is_ok()).collect();
+    if failed.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "Toolchain requirements not met:\n{}\n💡 Install or switch to the required versions, e.g. with nvm, fnm, volta or rustup\n💡 Run 'knot doctor' to check the toolchains of every app",
+        failed
+            .iter()
+            .map(|check| format!(
+                "  • {} {} ({}): {}",
+                check.requirement.tool,
+                check.requirement.version,
+                check.requirement.source,
+                check.outcome()
+            ))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+fn detect(tool: &str) -> Detected {
+    let output = match Command::new(tool).arg("--version").stdin(Stdio::null()).output() {
+        Ok(output) => output,
+        Err(_) => return Detected::Missing,
+    };
+    // Some tools print their version to stderr
+    let text = format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    match parse_version(&text) {
+        Some(version) => Detected::Found(version),
+        None => Detected::Unparsed(text.trim().lines().next().unwrap_or_default().to_string()),
+    }
+}
+
+/// First version number in `--version` output such as `v20.11.0`, `cargo 1.79.0 (ffa9cf99a
+/// 2024-06-03)` or `Python 3.12`; a missing patch counts as 0
+fn parse_version(output: &str) -> Option<Version> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").unwrap());
+    let captures = pattern.captures(output)?;
+    let number = |index: usize| captures.get(index).map_or(Some(0), |m| m.as_str().parse().ok());
+    Some(Version::new(number(1)?, number(2)?, number(3)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v20.11.0\n"), Some(Version::new(20, 11, 0)));
+        assert_eq!(parse_version("cargo 1.79.0 (ffa9cf99a 2024-06-03)"), Some(Version::new(1, 79, 0)));
+        assert_eq!(parse_version("Python 3.12"), Some(Version::new(3, 12, 0)));
+        assert_eq!(parse_version("unknown"), None);
+    }
+
+    #[test]
+    fn test_app_tools_replace_project_tools() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("knot.yml"), "name: demo\ntools:\n  node: \">=20\"\n  sh: \"*\"\n").unwrap();
+        fs::create_dir_all(root.join("apps/web")).unwrap();
+        fs::write(root.join("apps/web/app.yml"), "name: web\ntools:\n  node: \"^22\"\n").unwrap();
+        let project = Project::find_and_load(root).unwrap();
+
+        let requirements = requirements(&project, Some("web")).unwrap();
+        let declared: Vec<(&str, String, &str)> = requirements
+            .iter()
+            .map(|r| (r.tool.as_str(), r.version.to_string(), r.source.as_str()))
+            .collect();
+        assert_eq!(
+            declared,
+            vec![("node", "^22".to_string(), "app 'web'"), ("sh", "*".to_string(), "knot.yml")]
+        );
+
+        let check = ToolCheck {
+            requirement: requirements[0].clone(),
+            detected: Detected::Found(Version::new(20, 11, 0)),
+        };
+        assert!(!check.is_ok());
+        assert!(check.outcome().contains("20.11.0"));
+
+        // "*" only requires the tool to run
+        let any = ToolCheck {
+            requirement: requirements[1].clone(),
+            detected: Detected::Unparsed("usage: sh".to_string()),
+        };
+        assert!(any.is_ok());
+        assert_eq!(detect("knot-missing-tool"), Detected::Missing);
+    }
+}
@@ -14,12 +14,10 @@ impl<'a> TypeScriptManager<'a> {
         Self { project }
     }
 
-    pub fn setup_aliases_for_all_apps(&self) -> Result<()> {
-        let app_names = self.project.get_app_names();
-
+    pub fn setup_aliases_for_apps(&self, app_names: &[String]) -> Result<()> {
         for app_name in app_names {
-            if let Some(alias) = self.project.get_app_ts_alias(&app_name) {
-                self.setup_tsconfig_alias(&app_name, &alias)?;
+            if let Some(alias) = self.project.get_app_ts_alias(app_name) {
+                self.setup_tsconfig_alias(app_name, &alias)?;
             }
         }
         Ok(())
@@ -1,4 +1,37 @@
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Options of the global flags, shared by every command
+#[derive(Debug, Default)]
+pub struct GlobalOptions {
+    /// `--offline`
+    pub offline: bool,
+    /// `--ignore-scripts`
+    pub ignore_scripts: bool,
+    /// `--env-profile`, selecting the `.env.<profile>` files
+    pub env_profile: Option<String>,
+    /// `--env-file`, resolved against the directory knot was started in
+    pub env_files: Vec<PathBuf>,
+}
+
+static GLOBAL_OPTIONS: OnceLock<GlobalOptions> = OnceLock::new();
+
+/// Record the global flags once, before any command runs
+pub fn set_global_options(options: GlobalOptions) {
+    let _ = GLOBAL_OPTIONS.set(options);
+}
+
+/// The global flags, or their defaults when they were never set (e.g. in tests)
+pub fn global_options() -> &'static GlobalOptions {
+    GLOBAL_OPTIONS.get_or_init(GlobalOptions::default)
+}
+
+/// Whether an environment variable is set to `1`, `true` or `yes`
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
 
 /// Find a YAML config file with either .yml or .yaml extension
 /// Returns the path to the first file found, prioritizing .yml
@@ -19,6 +52,29 @@ pub fn get_yaml_config_path(base_path: &Path, filename: &str) -> PathBuf {
         .unwrap_or_else(|| base_path.join(format!("{}.yml", filename)))
 }
 
+/// Whether network access is disabled, either by the global `--offline` flag or `KNOT_OFFLINE=1`
+pub fn is_offline() -> bool {
+    global_options().offline || env_flag("KNOT_OFFLINE")
+}
+
+/// Whether `pre<script>`/`post<script>` hooks are disabled, either by the global
+/// `--ignore-scripts` flag or `KNOT_IGNORE_SCRIPTS=1`
+pub fn ignore_scripts() -> bool {
+    global_options().ignore_scripts || env_flag("KNOT_IGNORE_SCRIPTS")
+}
+
+/// Directory for caches shared by every project of the current user (~/.knot/cache),
+/// overridable with `KNOT_CACHE_DIR`
+pub fn user_cache_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("KNOT_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+    dirs::home_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(".knot")
+        .join("cache")
+}
+
 /// Check if a YAML config file exists with either extension
 pub fn yaml_config_exists(base_path: &Path, filename: &str) -> bool {
     find_yaml_file(base_path, filename).is_some()
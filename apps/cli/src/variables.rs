@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use anyhow::{Context, Result};
-use crate::config::{KnotConfig, AppConfig, PackageConfig};
+use crate::config::{KnotConfig, AppConfig, PackageConfig, Script};
 #[cfg(test)]
 use crate::config::ConfigVariable;
 
@@ -162,7 +162,11 @@ impl std::fmt::Display for VariableSource {
     }
 }
 
-/// Interpolate variables in a string using the {{variable_name}} syntax
+/// Variables filled in when a script runs rather than when the config is loaded
+const RUNTIME_VARIABLES: [&str; 1] = ["args"];
+
+/// Interpolate variables in a string using the {{variable_name}} syntax.
+/// Runtime variables such as `{{args}}` are left in place.
 pub fn interpolate_variables(text: &str, context: &VariableContext) -> Result<String> {
     let mut result = text.to_string();
     let mut missing_variables = Vec::new();
@@ -175,6 +179,9 @@ pub fn interpolate_variables(text: &str, context: &VariableContext) -> Result<St
         let full_match = captures.get(0).unwrap().as_str();
         let var_name = captures.get(1).unwrap().as_str();
         
+        if RUNTIME_VARIABLES.contains(&var_name) {
+            continue;
+        }
         if let Some(value) = context.get_variable(var_name) {
             result = result.replace(full_match, value);
         } else {
@@ -214,6 +221,34 @@ impl VariableInterpolation for HashMap<String, String> {
     }
 }
 
+impl VariableInterpolation for HashMap<String, Script> {
+    fn interpolate_variables(&mut self, context: &VariableContext) -> Result<()> {
+        for (name, script) in self.iter_mut() {
+            script.interpolate_variables(context)
+                .with_context(|| format!("Failed to interpolate variables in script '{}'", name))?;
+        }
+        Ok(())
+    }
+}
+
+impl VariableInterpolation for Script {
+    fn interpolate_variables(&mut self, context: &VariableContext) -> Result<()> {
+        match self {
+            Script::Command(command) => *command = interpolate_variables(command, context)?,
+            Script::Detailed { run, description, cwd, env, shell, inputs, outputs, .. } => {
+                *run = interpolate_variables(run, context)?;
+                description.interpolate_variables(context)?;
+                cwd.interpolate_variables(context)?;
+                env.interpolate_variables(context)?;
+                shell.interpolate_variables(context)?;
+                inputs.interpolate_variables(context)?;
+                outputs.interpolate_variables(context)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl VariableInterpolation for Vec<String> {
     fn interpolate_variables(&mut self, context: &VariableContext) -> Result<()> {
         for (index, value) in self.iter_mut().enumerate() {
@@ -264,6 +299,10 @@ impl VariableInterpolation for AppConfig {
                 .context("Failed to interpolate app packages")?;
         }
         
+        // Interpolate build command
+        self.build.interpolate_variables(context)
+            .context("Failed to interpolate app build command")?;
+
         // Interpolate scripts
         if let Some(scripts) = &mut self.scripts {
             scripts.interpolate_variables(context)
@@ -369,18 +408,24 @@ mod tests {
                     vars.insert("test_var".to_string(), ConfigVariable::Simple("project".to_string()));
                     vars
                 }),
+                cache: None,
+                remote_cache: None,
+                tools: None,
             })
             .with_app_variables(&AppConfig {
                 name: "test-app".to_string(),
                 description: None,
                 ts_alias: None,
                 packages: None,
+                build: None,
                 scripts: None,
+                dev: None,
                 variables: Some({
                     let mut vars = HashMap::new();
                     vars.insert("test_var".to_string(), ConfigVariable::Simple("app".to_string()));
                     vars
                 }),
+                tools: None,
             });
         
         // App variable should override project variable
@@ -400,6 +445,10 @@ mod tests {
         // Test missing variable
         let result = interpolate_variables("Hello {{missing_var}}!", &context);
         assert!(result.is_err());
+
+        // Runtime variables are kept for the script runner
+        let result = interpolate_variables("{{project_name}} {{args}}", &context).unwrap();
+        assert_eq!(result, "my-project {{args}}");
     }
     
     #[test]
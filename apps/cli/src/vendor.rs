@@ -0,0 +1,259 @@
+use crate::downloader::PackageDownloader;
+use anyhow::{Context, Result};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to the project root) holding vendored Knot Space packages
+pub const VENDOR_DIR: &str = "vendor/knot";
+const MANIFEST_FILE: &str = "manifest.yml";
+const TARBALL_FILE: &str = "package.tar.gz";
+
+/// Index of every vendored package, stored as vendor/knot/manifest.yml
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VendorManifest {
+    /// Vendored packages keyed by "name@version"
+    pub packages: BTreeMap<String, VendoredPackage>,
+}
+
+/// A single vendored package tarball
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendoredPackage {
+    /// Package name including the leading @ (e.g. @team/utils)
+    pub name: String,
+    /// Concrete version that was downloaded
+    pub version: String,
+    /// SHA-256 of the vendored tarball
+    pub checksum: String,
+}
+
+/// Read/write access to the vendor/knot directory, used as an offline registry
+pub struct VendorStore {
+    root: PathBuf,
+}
+
+impl VendorStore {
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            root: project_root.join(VENDOR_DIR),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn load_manifest(&self) -> Result<VendorManifest> {
+        let manifest_path = self.root.join(MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Ok(VendorManifest::default());
+        }
+
+        let content = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read vendor manifest: {}", manifest_path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse vendor manifest: {}", manifest_path.display()))
+    }
+
+    fn save_manifest(&self, manifest: &VendorManifest) -> Result<()> {
+        fs::create_dir_all(&self.root)?;
+        let content = serde_yaml::to_string(manifest)?;
+        fs::write(self.root.join(MANIFEST_FILE), content)
+            .context("Failed to write vendor manifest")?;
+        Ok(())
+    }
+
+    /// Directory for one package version, e.g. vendor/knot/team/utils/1.2.0
+    fn package_dir(&self, name: &str, version: &str) -> PathBuf {
+        self.root.join(name.trim_start_matches('@')).join(version)
+    }
+
+    /// Download every given `@package` spec and replace the vendor directory contents
+    pub async fn vendor_all(&self, package_specs: &[String]) -> Result<VendorManifest> {
+        let mut manifest = VendorManifest::default();
+
+        for spec in package_specs {
+            let (name, requested) = PackageDownloader::parse_package_spec(spec);
+            let (version, tarball) = PackageDownloader::fetch_package_tarball(&name, requested.as_deref())
+                .await
+                .with_context(|| format!("Failed to vendor package '{}'", spec))?;
+
+            let key = format!("{}@{}", name, version);
+            if manifest.packages.contains_key(&key) {
+                continue;
+            }
+
+            let dir = self.package_dir(&name, &version);
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create vendor directory: {}", dir.display()))?;
+            fs::write(dir.join(TARBALL_FILE), &tarball)
+                .with_context(|| format!("Failed to write vendored tarball for {}", key))?;
+
+            println!("📥 Vendored {}", key);
+            manifest.packages.insert(
+                key,
+                VendoredPackage {
+                    name,
+                    version,
+                    checksum: checksum(&tarball),
+                },
+            );
+        }
+
+        // Drop versions that are no longer referenced by any app
+        self.prune(&manifest)?;
+        self.save_manifest(&manifest)?;
+        Ok(manifest)
+    }
+
+    fn prune(&self, manifest: &VendorManifest) -> Result<()> {
+        let previous = self.load_manifest()?;
+        for (key, package) in previous.packages {
+            if !manifest.packages.contains_key(&key) {
+                let dir = self.package_dir(&package.name, &package.version);
+                if dir.exists() {
+                    fs::remove_dir_all(&dir)
+                        .with_context(|| format!("Failed to remove stale vendored package: {}", dir.display()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the vendored package that best satisfies a `@name[@version]` spec
+    pub fn find(&self, package_spec: &str) -> Result<Option<VendoredPackage>> {
+        let (name, requested) = PackageDownloader::parse_package_spec(package_spec);
+        let manifest = self.load_manifest()?;
+
+        let candidates = manifest
+            .packages
+            .into_values()
+            .filter(|p| p.name == name)
+            .filter_map(|p| Version::parse(&p.version).ok().map(|v| (v, p)));
+
+        let best = match requested.as_deref() {
+            None | Some("latest") => candidates.max_by(|a, b| a.0.cmp(&b.0)),
+            Some(requested) => {
+                if let Ok(exact) = Version::parse(requested) {
+                    candidates.filter(|(v, _)| *v == exact).max_by(|a, b| a.0.cmp(&b.0))
+                } else {
+                    let req = VersionReq::parse(requested)
+                        .with_context(|| format!("Invalid version requirement '{}' for '{}'", requested, name))?;
+                    candidates.filter(|(v, _)| req.matches(v)).max_by(|a, b| a.0.cmp(&b.0))
+                }
+            }
+        };
+
+        Ok(best.map(|(_, p)| p))
+    }
+
+    /// Extract a vendored package into `destination` after verifying its checksum
+    pub fn install(&self, package_spec: &str, destination: &Path) -> Result<()> {
+        let package = self.find(package_spec)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Package '{}' is not vendored in {}\n💡 Run 'knot vendor' while online to download remote packages\n💡 Then commit the {} directory",
+                package_spec,
+                VENDOR_DIR,
+                VENDOR_DIR
+            )
+        })?;
+
+        let tarball_path = self.package_dir(&package.name, &package.version).join(TARBALL_FILE);
+        let tarball = fs::read(&tarball_path)
+            .with_context(|| format!("Failed to read vendored tarball: {}", tarball_path.display()))?;
+
+        let actual = checksum(&tarball);
+        if actual != package.checksum {
+            anyhow::bail!(
+                "Checksum mismatch for vendored package {}@{}\n💡 Expected {}, found {}\n💡 Re-run 'knot vendor' to refresh the vendored copy",
+                package.name, package.version, package.checksum, actual
+            );
+        }
+
+        PackageDownloader::extract_tarball(&tarball, destination)?;
+        Ok(())
+    }
+}
+
+fn checksum(data: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::TempDir;
+
+    fn make_tarball(file_name: &str, content: &str) -> Vec<u8> {
+        let encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, file_name, content.as_bytes()).unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn vendor_version(store: &VendorStore, manifest: &mut VendorManifest, name: &str, version: &str) {
+        let tarball = make_tarball("index.ts", version);
+        let dir = store.package_dir(name, version);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(TARBALL_FILE), &tarball).unwrap();
+        manifest.packages.insert(
+            format!("{}@{}", name, version),
+            VendoredPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                checksum: checksum(&tarball),
+            },
+        );
+    }
+
+    #[test]
+    fn test_find_selects_best_matching_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VendorStore::new(temp_dir.path());
+        let mut manifest = VendorManifest::default();
+        vendor_version(&store, &mut manifest, "@team/utils", "1.2.0");
+        vendor_version(&store, &mut manifest, "@team/utils", "1.4.1");
+        vendor_version(&store, &mut manifest, "@team/utils", "2.0.0");
+        store.save_manifest(&manifest).unwrap();
+
+        let latest = store.find("@team/utils").unwrap().unwrap();
+        assert_eq!(latest.version, "2.0.0");
+
+        let caret = store.find("@team/utils@^1.2.0").unwrap().unwrap();
+        assert_eq!(caret.version, "1.4.1");
+
+        let exact = store.find("@team/utils@1.2.0").unwrap().unwrap();
+        assert_eq!(exact.version, "1.2.0");
+
+        assert!(store.find("@team/utils@3.0.0").unwrap().is_none());
+        assert!(store.find("@other").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_install_verifies_checksum() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VendorStore::new(temp_dir.path());
+        let mut manifest = VendorManifest::default();
+        vendor_version(&store, &mut manifest, "@jwt", "0.3.0");
+        store.save_manifest(&manifest).unwrap();
+
+        let destination = temp_dir.path().join("knot_packages").join("jwt");
+        store.install("@jwt", &destination).unwrap();
+        assert_eq!(fs::read_to_string(destination.join("index.ts")).unwrap(), "0.3.0");
+
+        // Tamper with the vendored tarball
+        let tarball_path = store.package_dir("@jwt", "0.3.0").join(TARBALL_FILE);
+        fs::write(&tarball_path, make_tarball("index.ts", "tampered")).unwrap();
+        let error = store.install("@jwt", &temp_dir.path().join("other")).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"));
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::graph::{ProjectGraph, ProjectNode};
+use crate::ignore::KnotIgnore;
+use crate::project::Project;
+
+/// Quiet period after the last change before a script is re-run
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Never watched: knot's own state and the package copies written by `knot link`
+const ALWAYS_IGNORED: [&str; 3] = [".knot", ".git", "knot_packages"];
+
+/// Directories to watch for a script of `node`: its own directory plus every local package
+/// it depends on, directly or through other packages
+pub fn watch_dirs(project: &Project, graph: &ProjectGraph, node: &ProjectNode) -> Vec<PathBuf> {
+    graph
+        .with_dependencies(std::slice::from_ref(node))
+        .into_iter()
+        .map(|dependency| dependency.dir(&project.root))
+        .collect()
+}
+
+struct WatchedDir {
+    dir: PathBuf,
+    ignore: KnotIgnore,
+}
+
+/// Recursive file watcher reporting debounced changes, skipping `.knotignore`d paths
+pub struct ChangeWatcher {
+    dirs: Vec<WatchedDir>,
+    excluded: Vec<PathBuf>,
+    events: mpsc::UnboundedReceiver<PathBuf>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ChangeWatcher {
+    pub fn new(dirs: &[PathBuf]) -> Result<Self> {
+        let (sender, events) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if !matches!(event.kind, notify::EventKind::Access(_)) {
+                    for path in event.paths {
+                        let _ = sender.send(path);
+                    }
+                }
+            }
+        })
+        .context("Failed to start the file watcher")?;
+
+        let mut watched = Vec::new();
+        for dir in dirs {
+            // Events carry canonical paths on some platforms
+            let dir = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+            watcher
+                .watch(&dir, RecursiveMode::Recursive)
+                .with_context(|| format!("Failed to watch {}", dir.display()))?;
+            watched.push(WatchedDir {
+                ignore: KnotIgnore::from_file(&dir.join(".knotignore")).unwrap_or_else(|_| KnotIgnore::default()),
+                dir,
+            });
+        }
+
+        Ok(Self {
+            dirs: watched,
+            excluded: Vec::new(),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Also skip changes below `paths`, e.g. the outputs the script itself writes
+    pub fn with_excluded(mut self, paths: Vec<PathBuf>) -> Self {
+        self.excluded = paths
+            .into_iter()
+            .map(|path| path.canonicalize().unwrap_or(path))
+            .collect();
+        self
+    }
+
+    /// Forget changes reported so far, e.g. those made while a script's dependencies ran
+    pub fn clear(&mut self) {
+        while self.events.try_recv().is_ok() {}
+    }
+
+    /// Wait for a change, then until no further change arrives for [`DEBOUNCE`].
+    /// Returns the changed paths.
+    pub async fn next_change(&mut self) -> Vec<PathBuf> {
+        let mut changed = BTreeSet::new();
+        while changed.is_empty() {
+            match self.events.recv().await {
+                Some(path) if self.is_watched(&path) => {
+                    changed.insert(path);
+                }
+                Some(_) => {}
+                None => std::future::pending::<()>().await,
+            }
+        }
+
+        while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, self.events.recv()).await {
+            if self.is_watched(&path) {
+                changed.insert(path);
+            }
+        }
+        changed.into_iter().collect()
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        if self.excluded.iter().any(|excluded| path.starts_with(excluded)) {
+            return false;
+        }
+
+        self.dirs.iter().any(|watched| {
+            let Ok(relative) = path.strip_prefix(&watched.dir) else {
+                return false;
+            };
+
+            // A path is skipped when it or any of its parent directories is ignored
+            let mut prefix = String::new();
+            for component in relative.components() {
+                let part = component.as_os_str().to_string_lossy();
+                if ALWAYS_IGNORED.contains(&part.as_ref()) {
+                    return false;
+                }
+                if !prefix.is_empty() {
+                    prefix.push('/');
+                }
+                prefix.push_str(&part);
+                if watched.ignore.is_ignored(&prefix) {
+                    return false;
+                }
+            }
+            !prefix.is_empty()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_reports_debounced_changes_outside_ignored_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("dist")).unwrap();
+        fs::write(dir.join(".knotignore"), "coverage\n").unwrap();
+
+        let mut watcher = ChangeWatcher::new(std::slice::from_ref(&dir)).unwrap().with_excluded(vec![dir.join("dist")]);
+        assert!(watcher.is_watched(&dir.join("src/index.ts")));
+        assert!(!watcher.is_watched(&dir.join("coverage/lcov.info")));
+        assert!(!watcher.is_watched(&dir.join("dist/index.js")));
+        assert!(!watcher.is_watched(&dir.join("knot_packages/utils/index.ts")));
+        assert!(!watcher.is_watched(Path::new("/elsewhere/index.ts")));
+
+        fs::write(dir.join("dist/index.js"), "built").unwrap();
+        fs::write(dir.join("src/index.ts"), "export {};").unwrap();
+        fs::write(dir.join("src/other.ts"), "export {};").unwrap();
+        let changed = tokio::time::timeout(Duration::from_secs(10), watcher.next_change())
+            .await
+            .expect("no change reported");
+        assert!(changed.contains(&dir.join("src/index.ts")));
+        assert!(changed.iter().all(|path| path.starts_with(dir.join("src"))));
+    }
+}